@@ -1,9 +1,8 @@
+use std::time::Duration;
 
 use regui_iui::prelude::*;
 use controls::*;
-use regui::{function_component::Cx, decl_function_component};
-
-use tokio::task::spawn_local;
+use regui::{function_component::Cx, decl_function_component, task::{self, Resource}};
 
 #[tokio::main]
 async fn main() {
@@ -14,44 +13,52 @@ async fn main() {
 decl_function_component!(Ui ui(UI) -> ());
 
 fn ui(ui: &UI, cx: &mut Cx) -> () {
-    let counter = cx.use_state(|| 0);
-
-    cx.use_ref(|| {
-        // increment counter after 2 second
-        spawn_local({
-            let counter = counter.clone();
-            async move {
-                tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-                counter.set(counter.get() + 42);
-            }
-        });
+    // computed once, after 2 seconds
+    let answer = cx.use_future((), async {
+        task::sleep(Duration::from_secs(2)).await;
+        42
+    });
+
+    // restarted on every click, the previous one is aborted
+    let clicks = cx.use_state(|| 0);
+    let delayed_clicks = cx.use_future(clicks.get(), {
+        let clicks = clicks.get();
+        async move {
+            task::sleep(Duration::from_secs(1)).await;
+            clicks
+        }
     });
 
     let button = Button::builder(ui)
-        .text(&"Increment after 1 second")
+        .text(&"Click")
         .on_click({
-            let counter = counter.clone();
-            move |_| {
-                let counter = counter.clone();
-                spawn_local(async move {
-                    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-                    counter.set(counter.get() + 1);
-                });
-            }
+            let clicks = clicks.clone();
+            move |_| clicks.set(clicks.get() + 1)
+        })
+        .get(cx);
+
+    let answer_label = Label::builder(ui)
+        .text(&match answer {
+            Resource::Ready(answer) => format!("The answer is {}", answer),
+            _ => "Computing the answer...".to_string(),
         })
         .get(cx);
 
-    let label = Label::builder(ui)
-        .text(&format!("Counter: {}", counter.get()))
+    let clicks_label = Label::builder(ui)
+        .text(&match delayed_clicks {
+            Resource::Ready(clicks) => format!("Clicked {} times, one second ago", clicks),
+            _ => "Waiting for one second without clicks...".to_string(),
+        })
         .get(cx);
 
     let vbox = VerticalBox::builder(ui)
-        .child(label, LayoutStrategy::Compact)
+        .child(answer_label, LayoutStrategy::Compact)
+        .child(clicks_label, LayoutStrategy::Compact)
         .child(button, LayoutStrategy::Compact)
         .get(cx);
 
     let _win = Window::builder(ui)
-        .title(&counter.get().to_string())
+        .title("Async")
         .child(vbox)
         .get(cx);
-}
\ No newline at end of file
+}
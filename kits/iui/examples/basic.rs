@@ -1,7 +1,7 @@
 
 use regui_iui::prelude::*;
 use controls::*;
-use regui::{function_component::Cx, decl_function_component};
+use regui::{function_component::Cx, decl_function_component, view};
 
 #[tokio::main]
 async fn main() {
@@ -14,18 +14,16 @@ decl_function_component!(Ui ui(UI) -> ());
 fn ui(ui: &UI, cx: &mut Cx) -> () {
     let counter = cx.use_state(|| 0);
 
-    let button = Button::builder(ui)
-        .text(&format!("Counter: {}", counter.get()))
-        .on_click({
-            let counter = counter.clone();
-            move |_btn| {
-                counter.set(counter.get() + 1);
-            }
-        })
-        .get(cx);
+    let on_click = {
+        let counter = counter.clone();
+        move |_btn: &_| {
+            counter.set(counter.get() + 1);
+        }
+    };
 
-    let _win = Window::builder(ui)
-        .title(&counter.get().to_string())
-        .child(button)
-        .get(cx);
-}
\ No newline at end of file
+    view! { cx, ui =>
+        <Window title={&counter.get().to_string()}>
+            <Button text={&format!("Counter: {}", counter.get())} on_click={on_click} />
+        </Window>
+    };
+}
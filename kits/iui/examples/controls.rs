@@ -8,11 +8,11 @@ use std::time::Duration;
 use regui_iui::prelude::*;
 use regui_iui::controls::{Button, Window, VerticalBox, Checkbox, Combobox, Entry, Group, HorizontalBox, HorizontalSeparator, Label, MultilineEntry, Spacer, PasswordEntry};
 
-fn timeout(timeout: Duration, f: impl Future + 'static) {
+fn timeout(timeout: Duration, f: impl Future + 'static) -> tokio::task::JoinHandle<()> {
     tokio::task::spawn_local(async move {
         tokio::time::sleep(timeout).await;
         f.await;
-    });
+    })
 }
 
 #[tokio::main]
@@ -28,13 +28,14 @@ fn ui(ui: &UI, cx: &mut Cx) -> () {
 
     let counter = cx.use_state(|| 0);
 
-    cx.use_ref(|| {
-        timeout(Duration::from_secs(1), {
-            let counter = counter.clone();
-            async move {
+    cx.use_effect((), {
+        let counter = counter.clone();
+        move |_| {
+            let task = timeout(Duration::from_secs(1), async move {
                 counter.set(counter.get() + 42);
-            }
-        });
+            });
+            move || task.abort()
+        }
     });
 
     let button_1 = Button::builder(ui)
@@ -1,3 +1,4 @@
+use regui::function_component::UseStateHandle;
 
 mod window; pub use window::Window;
 
@@ -14,4 +15,11 @@ mod multiline_entry; pub use multiline_entry::MultilineEntry;
 mod password_entry; pub use password_entry::PasswordEntry;
 mod slider; pub use slider::Slider;
 mod spacer; pub use spacer::Spacer;
-mod vertical_box; pub use vertical_box::VerticalBox;
\ No newline at end of file
+mod vertical_box; pub use vertical_box::VerticalBox;
+
+/// Stores the props of this view, to compare them with the props of the next one.
+///
+/// The value is changed in place: [`set`](UseStateHandle::set) would render the control again.
+fn remember_props<T: Clone>(old: &UseStateHandle<T>, props: &T) {
+    old.on_mut_value(|old| *old = props.clone());
+}
\ No newline at end of file
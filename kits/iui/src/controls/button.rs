@@ -1,6 +1,6 @@
 use std::rc::Rc;
 
-use regui::{decl_function_component, function_component::{Cx, ComponentFunction}};
+use regui::{decl_function_component, component::batch, function_component::{Cx, ComponentFunction}};
 use iui::{controls::Button as IuiButton, UI};
 
 use crate::Control;
@@ -57,7 +57,7 @@ fn button(props: &ButtonProps, cx: &mut Cx) -> Control {
     button.on_clicked(&props.ui, {
         let on_click = props.on_click.clone();
         move |btn| {
-            on_click(btn);
+            batch(|| on_click(btn));
         }
     });
 
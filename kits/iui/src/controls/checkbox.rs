@@ -1,7 +1,7 @@
 use std::rc::Rc;
 
 use iui::UI;
-use regui::{decl_function_component, function_component::{Cx, ComponentFunction}};
+use regui::{decl_function_component, component::batch, function_component::{Cx, ComponentFunction}};
 
 use crate::Control;
 
@@ -65,7 +65,7 @@ fn checkbox(props: &CheckboxProps, cx: &mut Cx) -> Control {
     checkbox.on_toggled(&props.ui, {
         let on_toggled = props.on_toggled.clone();
         move |checked| {
-            on_toggled(checked);
+            batch(|| on_toggled(checked));
         }
     });
 
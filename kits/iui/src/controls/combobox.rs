@@ -1,9 +1,9 @@
 use std::rc::Rc;
 
 use iui::UI;
-use regui::{decl_function_component, function_component::{Cx, ComponentFunction}};
+use regui::{decl_function_component, component::batch, function_component::{Cx, ComponentFunction}};
 
-use crate::Control;
+use crate::{Control, controls::remember_props};
 
 pub struct ComboboxProps {
     pub ui: UI,
@@ -71,14 +71,14 @@ fn combobox(props: &ComboboxProps, cx: &mut Cx) -> Control {
     if old_items.get() != props.items {
         todo!("Combobox items change not implemented");
     }
-    old_items.set(props.items.clone());
+    remember_props(&old_items, &props.items);
 
     combobox.set_selected(&props.ui, props.selected as i32);
 
     combobox.on_selected(&props.ui, {
         let on_selected = props.on_selected.clone();
         move |selected| {
-            on_selected(selected as usize);
+            batch(|| on_selected(selected as usize));
         }
     });
 
@@ -5,7 +5,7 @@ use iui::controls::HorizontalBox as IuiHorizontalBox;
 use regui::function_component::{Cx, ComponentFunction};
 use regui::decl_function_component;
 
-use crate::Control;
+use crate::{Control, controls::remember_props};
 
 pub struct Strategy(LayoutStrategy);
 
@@ -88,7 +88,7 @@ fn horizontal_box(props: &VertialBoxProps, cx: &mut Cx) -> Control {
         todo!()
     }
 
-    old_children.set(props.children.clone());
+    remember_props(&old_children, &props.children);
 
     control.deref().clone()
 }
\ No newline at end of file
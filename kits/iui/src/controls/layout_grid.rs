@@ -1,7 +1,7 @@
 use iui::UI;
 use regui::{decl_function_component, function_component::{Cx, ComponentFunction}};
 
-use crate::Control;
+use crate::{Control, controls::remember_props};
 
 #[derive(Clone, PartialEq)]
 pub struct LayoutPosition {
@@ -116,7 +116,7 @@ fn layout_grid(props: &LayoutGridProps, cx: &mut Cx) -> Control {
         todo!()
     }
 
-    old_children.set(props.children.clone());
+    remember_props(&old_children, &props.children);
 
     layout_grid.set_padded(&props.ui, props.padded);
 
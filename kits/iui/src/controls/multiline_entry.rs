@@ -1,7 +1,7 @@
 use std::{rc::Rc, cell::RefCell};
 
 use iui::{UI, prelude::TextEntry};
-use regui::{decl_function_component, function_component::{Cx, ComponentFunction}};
+use regui::{decl_function_component, component::batch, function_component::{Cx, ComponentFunction}};
 
 use crate::Control;
 
@@ -61,7 +61,7 @@ fn multiline_entry(props: &MultilineEntryProps, cx: &mut Cx) -> Control {
     multiline_entry.on_changed(&props.ui, {
         let on_changed = props.on_changed.clone();
         move |entry| {
-            on_changed(entry);
+            batch(|| on_changed(entry));
         }
     });
 
@@ -3,7 +3,7 @@ use std::rc::Rc;
 use iui::{UI, prelude::NumericEntry};
 use iui::controls::Slider as IuiSlider;
 use regui::function_component::ComponentFunction;
-use regui::{decl_function_component, function_component::Cx};
+use regui::{decl_function_component, component::batch, function_component::Cx};
 
 use crate::Control;
 
@@ -72,7 +72,7 @@ fn slider(props: &SliderProps, cx: &mut Cx) -> Control {
     slider.on_changed(&props.ui, {
         let on_changed = props.on_changed.clone();
         move |value| {
-            on_changed(value);
+            batch(|| on_changed(value));
         }
     });
 
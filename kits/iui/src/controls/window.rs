@@ -6,7 +6,7 @@ use iui::prelude::WindowType;
 use regui::decl_function_component;
 use regui::function_component::{Cx, ComponentFunction};
 
-use crate::Control;
+use crate::{Control, controls::remember_props};
 
 
 pub struct WindowProps {
@@ -77,5 +77,5 @@ fn window(props: &WindowProps, cx: &mut Cx) -> () {
         }
     }
 
-    old_child.set(props.child.clone());
+    remember_props(&old_child, &props.child);
 }
\ No newline at end of file
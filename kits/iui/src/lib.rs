@@ -1,11 +1,11 @@
-use std::rc::Rc;
+use std::{rc::Rc, time::Duration};
 
 pub mod controls;
 
 pub use iui;
 use iui::UI;
 pub use regui;
-use regui::{component::{Component, LiveStateComponent}, StateFunction, function_component::{ComponentFunction, FunctionComponent}};
+use regui::{task::{LocalFuture, Spawner, set_spawner}, component::{Component, LiveStateComponent, batch, drain_sent_updates}, StateFunction, function_component::{ComponentFunction, FunctionComponent}};
 pub use tokio;
 
 pub mod prelude {
@@ -43,9 +43,22 @@ pub async fn run_ui<F: ComponentFunction>(props: F::Props, ui: &UI) {
     run_ui_component::<FunctionComponent<F>>(props, ui).await
 }
 
+/// Runs the futures of the components in the `LocalSet` of [`run_ui_component`].
+struct TokioSpawner;
+
+impl Spawner for TokioSpawner {
+    fn spawn_local(&self, future: LocalFuture) {
+        tokio::task::spawn_local(future);
+    }
+    fn sleep(&self, duration: Duration) -> LocalFuture {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
 pub async fn run_ui_component<UiComponent: Component>(props: UiComponent::Props, ui: &UI) {
     let local = tokio::task::LocalSet::new();
     local.run_until(async move {
+        set_spawner(TokioSpawner);
         //tokio::task::spawn_local
         let (
             _out,
@@ -59,6 +72,8 @@ pub async fn run_ui_component<UiComponent: Component>(props: UiComponent::Props,
             if !el.next_tick(ui) {
                 break;
             }
+            // the loop does not block, so no wake hook is needed
+            batch(drain_sent_updates);
             tokio::task::yield_now().await;
         }
     }).await;
@@ -9,7 +9,7 @@ use std::rc::Rc;
 
 
 use native_windows_gui as nwg;
-use regui::component::{LiveStateComponent, Component, StateLink, FunctionsCache, GetFromCache};
+use regui::component::{LiveStateComponent, Command, Component, StateLink, FunctionsCache, GetFromCache, ShouldRender};
 use regui_nwg::{NwgNode, components::{Window, Button, Label, TextInput}, run_ui, WindowEvent};
 
 fn main() {
@@ -31,7 +31,7 @@ impl Component for UiState {
     type Out = ();
     type Message = String;
 
-    fn build(_props: Self::Props) -> Self {
+    fn build(_props: Self::Props, _link: StateLink<Self>) -> Self {
         const LOGO_PNG: &[u8] = include_bytes!("logo.png");
         let icon = nwg::Icon::from_bin(LOGO_PNG).expect("Failed to load icon");
         Self {
@@ -40,8 +40,9 @@ impl Component for UiState {
         }
     }
 
-    fn on_message(&mut self, message: Self::Message) {
+    fn on_message(&mut self, message: Self::Message, _link: StateLink<Self>) -> Command<Self::Message> {
         self.title = message;
+        Command::none()
     }
 
     fn view(&self, link: StateLink<Self>, cache: &FunctionsCache) -> Self::Out {
@@ -90,7 +91,7 @@ impl Component for WindowContentState {
     type Props = WindowContent;
     type Out = Vec<NwgNode<nwg::ControlHandle>>;
     type Message = MyMessage;
-    fn build(props: Self::Props) -> Self {
+    fn build(props: Self::Props, _link: StateLink<Self>) -> Self {
         let title = "Hello world!";
         (props.change_text)(title);
         Self {
@@ -98,16 +99,18 @@ impl Component for WindowContentState {
             change_text: props.change_text,
         }
     }
-    fn update(&mut self, props: Self::Props) {
+    fn update(&mut self, props: Self::Props, _link: StateLink<Self>) -> ShouldRender {
         self.change_text = props.change_text;
+        true
     }
-    fn on_message(&mut self, message: Self::Message) {
+    fn on_message(&mut self, message: Self::Message, _link: StateLink<Self>) -> Command<Self::Message> {
         match message {
             MyMessage::SetTitle(text) => {
                 self.text = text;
                 (self.change_text)(&self.text);
             }
         }
+        Command::none()
     }
     fn view(&self, link: StateLink<Self>, cache: &FunctionsCache) -> Self::Out {
         //println!("view");
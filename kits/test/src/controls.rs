@@ -0,0 +1,7 @@
+mod window; pub use window::Window;
+
+mod button; pub use button::Button;
+mod entry; pub use entry::Entry;
+mod horizontal_box; pub use horizontal_box::HorizontalBox;
+mod label; pub use label::Label;
+mod vertical_box; pub use vertical_box::VerticalBox;
@@ -0,0 +1,64 @@
+use std::rc::Rc;
+
+use regui::{decl_function_component, function_component::{Cx, ComponentFunction}};
+
+use crate::{Node, NodeKind};
+
+pub struct ButtonProps {
+    pub id: Option<String>,
+    pub text: String,
+    pub on_click: Rc<dyn Fn()>,
+    pub enabled: bool,
+}
+
+impl Default for ButtonProps {
+    fn default() -> Self {
+        Self {
+            id: None,
+            text: "".into(),
+            on_click: Rc::new(|| {}),
+            enabled: true,
+        }
+    }
+}
+
+impl ButtonProps {
+    pub fn id(mut self, id: &str) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+    pub fn text(mut self, text: &str) -> Self {
+        self.text = text.into();
+        self
+    }
+    pub fn on_click(mut self, on_click: impl Fn() + 'static) -> Self {
+        self.on_click = Rc::new(on_click);
+        self
+    }
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+    pub fn get(self, cx: &mut Cx) -> Node {
+        Button::eval(cx, self)
+    }
+}
+
+decl_function_component!(pub Button button(ButtonProps) -> Node);
+
+impl Button {
+    pub fn builder() -> ButtonProps {
+        ButtonProps::default()
+    }
+}
+
+fn button(props: &ButtonProps, cx: &mut Cx) -> Node {
+    let node = cx.use_ref(|| Node::new(NodeKind::Button));
+
+    node.set_id(props.id.clone());
+    node.set_text(&props.text);
+    node.on_click(props.on_click.clone());
+    node.set_enabled(props.enabled);
+
+    (*node).clone()
+}
@@ -0,0 +1,74 @@
+use std::{rc::Rc, cell::RefCell};
+
+use regui::{decl_function_component, function_component::{Cx, ComponentFunction}};
+
+use crate::{Node, NodeKind};
+
+pub struct EntryProps {
+    pub id: Option<String>,
+    pub text: String,
+    pub on_changed: Rc<dyn Fn(String)>,
+    pub enabled: bool,
+}
+
+impl Default for EntryProps {
+    fn default() -> Self {
+        Self {
+            id: None,
+            text: "".into(),
+            on_changed: Rc::new(|_text| {}),
+            enabled: true,
+        }
+    }
+}
+
+impl EntryProps {
+    pub fn id(mut self, id: &str) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+    pub fn text(mut self, text: &str) -> Self {
+        self.text = text.into();
+        self
+    }
+    pub fn on_changed(mut self, on_changed: impl Fn(String) + 'static) -> Self {
+        self.on_changed = Rc::new(on_changed);
+        self
+    }
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+    pub fn get(self, cx: &mut Cx) -> Node {
+        Entry::eval(cx, self)
+    }
+}
+
+decl_function_component!(pub Entry entry(EntryProps) -> Node);
+
+impl Entry {
+    pub fn builder() -> EntryProps {
+        EntryProps::default()
+    }
+}
+
+fn entry(props: &EntryProps, cx: &mut Cx) -> Node {
+    let node = cx.use_ref(|| {
+        let node = Node::new(NodeKind::Entry);
+        node.set_text(&props.text);
+        node
+    });
+
+    // only overwrite what the user typed when the text prop itself changes
+    let old_text = cx.use_ref(|| RefCell::new(props.text.clone()));
+    if *old_text.borrow() != props.text {
+        node.set_text(&props.text);
+        *old_text.borrow_mut() = props.text.clone();
+    }
+
+    node.set_id(props.id.clone());
+    node.on_changed(props.on_changed.clone());
+    node.set_enabled(props.enabled);
+
+    (*node).clone()
+}
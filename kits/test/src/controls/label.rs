@@ -0,0 +1,40 @@
+use regui::{decl_function_component, function_component::{Cx, ComponentFunction}};
+
+use crate::{Node, NodeKind};
+
+#[derive(Default)]
+pub struct LabelProps {
+    pub id: Option<String>,
+    pub text: String,
+}
+
+impl LabelProps {
+    pub fn id(mut self, id: &str) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+    pub fn text(mut self, text: &str) -> Self {
+        self.text = text.into();
+        self
+    }
+    pub fn get(self, cx: &mut Cx) -> Node {
+        Label::eval(cx, self)
+    }
+}
+
+decl_function_component!(pub Label label(LabelProps) -> Node);
+
+impl Label {
+    pub fn builder() -> LabelProps {
+        LabelProps::default()
+    }
+}
+
+fn label(props: &LabelProps, cx: &mut Cx) -> Node {
+    let node = cx.use_ref(|| Node::new(NodeKind::Label));
+
+    node.set_id(props.id.clone());
+    node.set_text(&props.text);
+
+    (*node).clone()
+}
@@ -0,0 +1,44 @@
+use regui::{decl_function_component, function_component::{Cx, ComponentFunction}};
+
+use crate::{Node, NodeKind};
+
+#[derive(Default)]
+pub struct VerticalBoxProps {
+    pub id: Option<String>,
+    pub children: Vec<Node>,
+}
+
+impl VerticalBoxProps {
+    pub fn id(mut self, id: &str) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+    pub fn child(mut self, child: Node) -> Self {
+        self.children.push(child);
+        self
+    }
+    pub fn children(mut self, children: impl IntoIterator<Item = Node>) -> Self {
+        self.children.extend(children);
+        self
+    }
+    pub fn get(self, cx: &mut Cx) -> Node {
+        VerticalBox::eval(cx, self)
+    }
+}
+
+decl_function_component!(pub VerticalBox vertical_box(VerticalBoxProps) -> Node);
+
+impl VerticalBox {
+    pub fn builder() -> VerticalBoxProps {
+        VerticalBoxProps::default()
+    }
+}
+
+fn vertical_box(props: &VerticalBoxProps, cx: &mut Cx) -> Node {
+    let node = cx.use_ref(|| Node::new(NodeKind::VerticalBox));
+
+    node.set_id(props.id.clone());
+    node.set_children(props.children.clone());
+
+    (*node).clone()
+}
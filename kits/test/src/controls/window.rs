@@ -0,0 +1,50 @@
+use regui::{decl_function_component, function_component::{Cx, ComponentFunction}};
+
+use crate::{Node, NodeKind, open_window};
+
+#[derive(Default)]
+pub struct WindowProps {
+    pub id: Option<String>,
+    pub title: String,
+    pub child: Option<Node>,
+}
+
+impl WindowProps {
+    pub fn id(mut self, id: &str) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = title.into();
+        self
+    }
+    pub fn child(mut self, child: Node) -> Self {
+        self.child = Some(child);
+        self
+    }
+    pub fn get(self, cx: &mut Cx) -> Node {
+        Window::eval(cx, self)
+    }
+}
+
+decl_function_component!(pub Window window(WindowProps) -> Node);
+
+impl Window {
+    pub fn builder() -> WindowProps {
+        WindowProps::default()
+    }
+}
+
+fn window(props: &WindowProps, cx: &mut Cx) -> Node {
+    let node = cx.use_ref(|| {
+        let node = Node::new(NodeKind::Window);
+        open_window(&node);
+        node
+    });
+
+    node.set_id(props.id.clone());
+    node.set_text(&props.title);
+    node.set_children(props.child.iter().cloned().collect());
+
+    (*node).clone()
+}
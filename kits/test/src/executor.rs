@@ -0,0 +1,129 @@
+use std::{cell::{Cell, RefCell}, future::Future, pin::Pin, sync::{Arc, Mutex}, task::{Context, Poll, Wake, Waker}, time::Duration};
+
+use regui::task::{LocalFuture, Spawner};
+
+/// A single threaded executor with a virtual clock, so that the tests do not wait for real timers.
+#[derive(Default)]
+pub(crate) struct Executor {
+    tasks: RefCell<Vec<Option<LocalFuture>>>,
+    /// The tasks woken up since their last poll.
+    ready: Arc<Mutex<Vec<usize>>>,
+    now: Cell<Duration>,
+    timers: RefCell<Vec<(Duration, Waker)>>,
+}
+
+struct TaskWaker {
+    task: usize,
+    ready: Arc<Mutex<Vec<usize>>>,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.ready.lock().unwrap().push(self.task);
+    }
+}
+
+thread_local! {
+    static EXECUTOR: Executor = Executor::default();
+}
+
+impl Executor {
+    fn spawn(&self, future: LocalFuture) {
+        let mut tasks = self.tasks.borrow_mut();
+        self.ready.lock().unwrap().push(tasks.len());
+        tasks.push(Some(future));
+    }
+
+    /// Polls the ready tasks until none is ready, returns whether any task has been polled.
+    fn run_until_stalled(&self) -> bool {
+        let mut polled = false;
+        loop {
+            let ready = std::mem::take(&mut *self.ready.lock().unwrap());
+            if ready.is_empty() {
+                return polled;
+            }
+            for task in ready {
+                // the task can spawn other tasks while polled
+                let Some(mut future) = self.tasks.borrow_mut()[task].take() else {
+                    continue;
+                };
+                polled = true;
+                let waker = Waker::from(Arc::new(TaskWaker { task, ready: self.ready.clone() }));
+                if future.as_mut().poll(&mut Context::from_waker(&waker)).is_pending() {
+                    self.tasks.borrow_mut()[task] = Some(future);
+                }
+            }
+        }
+    }
+
+    /// The earliest timer elapsing before or at `time`.
+    fn next_deadline(&self, time: Duration) -> Option<Duration> {
+        self.timers.borrow().iter()
+            .map(|(deadline, _)| *deadline)
+            .filter(|deadline| *deadline <= time)
+            .min()
+    }
+
+    fn advance_to(&self, time: Duration) {
+        self.now.set(time.max(self.now.get()));
+        let now = self.now.get();
+        self.timers.borrow_mut().retain(|(deadline, waker)| {
+            if *deadline <= now {
+                waker.wake_by_ref();
+            }
+            *deadline > now
+        });
+    }
+}
+
+/// Runs the ready tasks of the current thread, returns whether any task has been polled.
+pub(crate) fn run_until_stalled() -> bool {
+    EXECUTOR.with(|executor| executor.run_until_stalled())
+}
+
+/// The virtual clock of the current thread.
+pub(crate) fn now() -> Duration {
+    EXECUTOR.with(|executor| executor.now.get())
+}
+
+/// The earliest sleep of the current thread elapsing before or at `time`.
+pub(crate) fn next_deadline(time: Duration) -> Option<Duration> {
+    EXECUTOR.with(|executor| executor.next_deadline(time))
+}
+
+/// Sets the virtual clock of the current thread, waking up the elapsed sleeps.
+pub(crate) fn advance_to(time: Duration) {
+    EXECUTOR.with(|executor| executor.advance_to(time));
+}
+
+/// The spawner of the executor of the current thread.
+pub(crate) struct TestSpawner;
+
+impl Spawner for TestSpawner {
+    fn spawn_local(&self, future: LocalFuture) {
+        EXECUTOR.with(|executor| executor.spawn(future));
+    }
+
+    fn sleep(&self, duration: Duration) -> LocalFuture {
+        let deadline = EXECUTOR.with(|executor| executor.now.get()) + duration;
+        Box::pin(Sleep { deadline })
+    }
+}
+
+struct Sleep {
+    deadline: Duration,
+}
+
+impl Future for Sleep {
+    type Output = ();
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        EXECUTOR.with(|executor| {
+            if executor.now.get() >= self.deadline {
+                Poll::Ready(())
+            } else {
+                executor.timers.borrow_mut().push((self.deadline, cx.waker().clone()));
+                Poll::Pending
+            }
+        })
+    }
+}
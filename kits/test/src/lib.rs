@@ -0,0 +1,348 @@
+use std::{rc::{Rc, Weak}, cell::RefCell, fmt::{self, Debug, Display}, any::Any, time::Duration};
+
+pub mod controls;
+mod executor;
+
+pub use regui;
+use regui::{task::set_spawner, component::{Component, LiveStateComponent, batch, drain_sent_updates}, StateFunction, function_component::{ComponentFunction, FunctionComponent}};
+
+pub mod prelude {
+    pub use crate::TestHarness;
+    pub use crate::{controls, Node, NodeKind};
+    pub use regui::decl_function_component;
+    pub use regui::function_component::component;
+    pub use regui::function_component::{State, FunctionComponent, Cx, ComponentFunction, Hook, HookCx, HookLink};
+}
+
+/// The kind of a [`Node`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    Window,
+    Button,
+    Label,
+    Entry,
+    VerticalBox,
+    HorizontalBox,
+}
+
+/// An in-memory control.
+///
+/// This is the test kit equivalent of a native control: it is created once by its component
+/// and then updated in place on every render. Like native controls, two nodes are equal only if
+/// they are the same node.
+#[derive(Clone)]
+pub struct Node {
+    inner: Rc<RefCell<NodeData>>,
+}
+
+struct NodeData {
+    kind: NodeKind,
+    id: Option<String>,
+    text: String,
+    enabled: bool,
+    children: Vec<Node>,
+    on_click: Option<Rc<dyn Fn()>>,
+    on_changed: Option<Rc<dyn Fn(String)>>,
+}
+
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl Node {
+    pub(crate) fn new(kind: NodeKind) -> Self {
+        Self {
+            inner: Rc::new(RefCell::new(NodeData {
+                kind,
+                id: None,
+                text: "".into(),
+                enabled: true,
+                children: vec![],
+                on_click: None,
+                on_changed: None,
+            })),
+        }
+    }
+
+    pub fn kind(&self) -> NodeKind {
+        self.inner.borrow().kind
+    }
+
+    pub fn id(&self) -> Option<String> {
+        self.inner.borrow().id.clone()
+    }
+
+    /// The text of the node: the title of a window, the text of a button, label or entry.
+    pub fn text(&self) -> String {
+        self.inner.borrow().text.clone()
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.inner.borrow().enabled
+    }
+
+    pub fn children(&self) -> Vec<Node> {
+        self.inner.borrow().children.clone()
+    }
+
+    /// Simulates a click on the node, the handler runs in a [`batch`] like in the other kits.
+    ///
+    /// # Panics
+    /// Panics if the node has no click handler or if it is disabled.
+    pub fn click(&self) {
+        let on_click = {
+            let data = self.inner.borrow();
+            assert!(data.enabled, "cannot click the disabled node {}", self.describe());
+            data.on_click.clone().unwrap_or_else(|| panic!("{} cannot be clicked", self.describe()))
+        };
+        batch(|| on_click());
+    }
+
+    /// Simulates the user replacing the text of the node.
+    ///
+    /// # Panics
+    /// Panics if the node does not accept text input or if it is disabled.
+    pub fn input(&self, text: &str) {
+        let on_changed = {
+            let data = self.inner.borrow();
+            assert!(data.enabled, "cannot type into the disabled node {}", self.describe());
+            data.on_changed.clone().unwrap_or_else(|| panic!("{} does not accept text input", self.describe()))
+        };
+        self.inner.borrow_mut().text = text.into();
+        batch(|| on_changed(text.into()));
+    }
+
+    /// Finds the first node in this subtree (this node included) matching `predicate`, depth first.
+    pub fn find(&self, predicate: &impl Fn(&Node) -> bool) -> Option<Node> {
+        if predicate(self) {
+            return Some(self.clone());
+        }
+        self.children().iter().find_map(|child| child.find(predicate))
+    }
+
+    pub fn find_by_id(&self, id: &str) -> Option<Node> {
+        self.find(&|node| node.id().as_deref() == Some(id))
+    }
+
+    pub fn find_by_text(&self, text: &str) -> Option<Node> {
+        self.find(&|node| node.text() == text)
+    }
+
+    fn describe(&self) -> String {
+        let data = self.inner.borrow();
+        match &data.id {
+            Some(id) => format!("{:?}#{}", data.kind, id),
+            None => format!("{:?} {:?}", data.kind, data.text),
+        }
+    }
+
+    fn fmt_tree(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        let data = self.inner.borrow();
+        write!(f, "{:indent$}{:?}", "", data.kind, indent = depth * 2)?;
+        if let Some(id) = &data.id {
+            write!(f, "#{}", id)?;
+        }
+        write!(f, " {:?}", data.text)?;
+        if !data.enabled {
+            write!(f, " (disabled)")?;
+        }
+        writeln!(f)?;
+        for child in &data.children {
+            child.fmt_tree(f, depth + 1)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn set_id(&self, id: Option<String>) {
+        self.inner.borrow_mut().id = id;
+    }
+
+    pub(crate) fn set_text(&self, text: &str) {
+        self.inner.borrow_mut().text = text.into();
+    }
+
+    pub(crate) fn set_enabled(&self, enabled: bool) {
+        self.inner.borrow_mut().enabled = enabled;
+    }
+
+    pub(crate) fn set_children(&self, children: Vec<Node>) {
+        self.inner.borrow_mut().children = children;
+    }
+
+    pub(crate) fn on_click(&self, on_click: Rc<dyn Fn()>) {
+        self.inner.borrow_mut().on_click = Some(on_click);
+    }
+
+    pub(crate) fn on_changed(&self, on_changed: Rc<dyn Fn(String)>) {
+        self.inner.borrow_mut().on_changed = Some(on_changed);
+    }
+}
+
+/// Formats the subtree, one node per line.
+///
+/// ```text
+/// Window "Counter"
+///   VerticalBox ""
+///     Label#count "0"
+///     Button#increment "Increment"
+/// ```
+impl Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_tree(f, 0)
+    }
+}
+
+impl Debug for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.describe())
+    }
+}
+
+thread_local! {
+    /// The open windows, this plays the role of the windowing system.
+    static WINDOWS: RefCell<Vec<Weak<RefCell<NodeData>>>> = const { RefCell::new(Vec::new()) };
+}
+
+pub(crate) fn open_window(window: &Node) {
+    WINDOWS.with(|windows| windows.borrow_mut().push(Rc::downgrade(&window.inner)));
+}
+
+/// Drives a root component without a native toolkit.
+///
+/// The harness plays the role of [`regui_iui::run_ui`](https://docs.rs/regui-iui): it builds the root component and keeps it alive.
+/// The windows the component opens can then be inspected and interacted with.
+/// Every interaction rerenders synchronously, so the tree can be asserted right after it.
+///
+/// The futures spawned by the components run on a single threaded executor with a virtual clock,
+/// see [`tick`](TestHarness::tick) and [`advance`](TestHarness::advance).
+///
+/// Windows are tracked per thread, as a windowing system would do, so a test should use a single harness at a time.
+///
+/// # Example
+/// ```
+/// use regui_test::prelude::*;
+/// use controls::{Button, Window};
+///
+/// decl_function_component!(Counter counter(()) -> ());
+///
+/// fn counter(_props: &(), cx: &mut Cx) {
+///     let count = cx.use_state(|| 0);
+///     let button = Button::builder()
+///         .id("increment")
+///         .text(&format!("Clicked {} times", count.get()))
+///         .on_click({
+///             let count = count.clone();
+///             move || count.set(count.get() + 1)
+///         })
+///         .get(cx);
+///     Window::builder().title("Counter").child(button).get(cx);
+/// }
+///
+/// let harness = TestHarness::new::<Counter>(());
+/// harness.click("increment");
+/// harness.click("increment");
+/// assert_eq!(harness.get_by_id("increment").text(), "Clicked 2 times");
+/// ```
+pub struct TestHarness {
+    _component: Box<dyn Any>,
+}
+
+impl TestHarness {
+    /// Builds the function component `F` as the root of the application.
+    pub fn new<F: ComponentFunction>(props: F::Props) -> Self {
+        Self::new_component::<FunctionComponent<F>>(props)
+    }
+
+    /// Builds the component `C` as the root of the application.
+    pub fn new_component<C: Component>(props: C::Props) -> Self {
+        set_spawner(executor::TestSpawner);
+        let (_out, component) = LiveStateComponent::<C>::build(props);
+        Self {
+            _component: Box::new(component),
+        }
+    }
+
+    /// The currently open windows, in opening order.
+    pub fn windows(&self) -> Vec<Node> {
+        WINDOWS.with(|windows| {
+            let mut windows = windows.borrow_mut();
+            windows.retain(|window| window.strong_count() > 0);
+            windows.iter()
+                .filter_map(|window| window.upgrade())
+                .map(|inner| Node { inner })
+                .collect()
+        })
+    }
+
+    /// The only open window.
+    ///
+    /// # Panics
+    /// Panics if there is not exactly one open window.
+    pub fn window(&self) -> Node {
+        let mut windows = self.windows();
+        assert_eq!(windows.len(), 1, "expected exactly one open window, found {:?}", windows);
+        windows.remove(0)
+    }
+
+    pub fn find_by_id(&self, id: &str) -> Option<Node> {
+        self.windows().iter().find_map(|window| window.find_by_id(id))
+    }
+
+    pub fn find_by_text(&self, text: &str) -> Option<Node> {
+        self.windows().iter().find_map(|window| window.find_by_text(text))
+    }
+
+    /// # Panics
+    /// Panics if no node has the given id, printing the current tree.
+    pub fn get_by_id(&self, id: &str) -> Node {
+        self.find_by_id(id).unwrap_or_else(|| panic!("no node with id {:?} in\n{}", id, self.tree()))
+    }
+
+    /// # Panics
+    /// Panics if no node has the given text, printing the current tree.
+    pub fn get_by_text(&self, text: &str) -> Node {
+        self.find_by_text(text).unwrap_or_else(|| panic!("no node with text {:?} in\n{}", text, self.tree()))
+    }
+
+    /// Clicks the node with the given id, see [`Node::click`].
+    pub fn click(&self, id: &str) {
+        self.get_by_id(id).click();
+    }
+
+    /// Types into the node with the given id, see [`Node::input`].
+    pub fn input(&self, id: &str, text: &str) {
+        self.get_by_id(id).input(text);
+    }
+
+    /// Runs a tick of the event loop: applies the updates sent from other threads through
+    /// [`SendLink`](regui::component::SendLink)s and polls the futures until they are all waiting.
+    pub fn tick(&self) {
+        loop {
+            batch(drain_sent_updates);
+            if !batch(executor::run_until_stalled) {
+                break;
+            }
+        }
+    }
+
+    /// Advances the virtual clock of the executor by `duration`, then runs a [`tick`](TestHarness::tick).
+    ///
+    /// The clock stops at every elapsed sleep to run a tick, so that the sleeps started meanwhile
+    /// (e.g. by an interval) elapse too.
+    pub fn advance(&self, duration: Duration) {
+        let target = executor::now() + duration;
+        while let Some(deadline) = executor::next_deadline(target) {
+            executor::advance_to(deadline);
+            self.tick();
+        }
+        executor::advance_to(target);
+        self.tick();
+    }
+
+    /// The tree of all the open windows, formatted as in [`Node`]'s [`Display`] implementation.
+    pub fn tree(&self) -> String {
+        self.windows().iter().map(|window| window.to_string()).collect()
+    }
+}
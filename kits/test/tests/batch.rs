@@ -0,0 +1,127 @@
+use std::{rc::Rc, cell::{Cell, RefCell}};
+
+use regui::component::batch;
+use regui_test::prelude::*;
+use controls::{Button, Label, VerticalBox, Window};
+
+decl_function_component!(Form form(Rc<Cell<usize>>) -> ());
+
+fn form(views: &Rc<Cell<usize>>, cx: &mut Cx) {
+    views.set(views.get() + 1);
+
+    let name = cx.use_state(|| "".to_string());
+    let age = cx.use_state(|| 0);
+    let saved = cx.use_state(|| false);
+
+    let summary = Label::builder()
+        .id("summary")
+        .text(&format!("{} {} {}", name.get(), age.get(), saved.get()))
+        .get(cx);
+    let fill = Button::builder()
+        .id("fill")
+        .on_click(move || {
+            name.set("Ada".into());
+            age.set(36);
+            saved.set(true);
+        })
+        .get(cx);
+
+    let vbox = VerticalBox::builder()
+        .child(summary)
+        .child(fill)
+        .get(cx);
+
+    Window::builder()
+        .child(vbox)
+        .get(cx);
+}
+
+#[test]
+fn event_callbacks_render_once() {
+    let views = Rc::new(Cell::new(0));
+    let harness = TestHarness::new::<Form>(views.clone());
+    let initial = views.get();
+
+    harness.click("fill");
+    assert_eq!(harness.get_by_id("summary").text(), "Ada 36 true");
+    assert_eq!(views.get(), initial + 1);
+}
+
+decl_function_component!(Nested nested(Rc<Cell<usize>>) -> ());
+
+fn nested(views: &Rc<Cell<usize>>, cx: &mut Cx) {
+    views.set(views.get() + 1);
+
+    let count = cx.use_state(|| 0);
+
+    let increment = Button::builder()
+        .id("increment")
+        .text(&count.get().to_string())
+        .on_click(move || {
+            batch(|| {
+                count.set(count.get() + 1);
+                count.set(count.get() + 1);
+            });
+            count.set(count.get() + 1);
+        })
+        .get(cx);
+
+    Window::builder()
+        .child(increment)
+        .get(cx);
+}
+
+#[test]
+fn nested_batches_render_when_the_outermost_returns() {
+    let views = Rc::new(Cell::new(0));
+    let harness = TestHarness::new::<Nested>(views.clone());
+    let initial = views.get();
+
+    harness.click("increment");
+    assert_eq!(harness.get_by_id("increment").text(), "3");
+    assert_eq!(views.get(), initial + 1);
+}
+
+#[test]
+fn components_built_in_a_batch_are_rendered_right_away() {
+    let views = Rc::new(Cell::new(0));
+    let harness = batch(|| {
+        let harness = TestHarness::new::<Form>(views.clone());
+        assert_eq!(harness.get_by_id("summary").text(), " 0 false");
+        harness
+    });
+
+    harness.click("fill");
+    assert_eq!(harness.get_by_id("summary").text(), "Ada 36 true");
+}
+
+decl_function_component!(Opener opener(Rc<RefCell<Option<TestHarness>>>) -> ());
+
+/// Builds another application from a click handler, e.g. to open a dialog.
+fn opener(dialog: &Rc<RefCell<Option<TestHarness>>>, cx: &mut Cx) {
+    let open = Button::builder()
+        .id("open")
+        .on_click({
+            let dialog = dialog.clone();
+            move || {
+                let harness = TestHarness::new::<Form>(Rc::new(Cell::new(0)));
+                dialog.borrow_mut().replace(harness);
+            }
+        })
+        .get(cx);
+
+    Window::builder()
+        .child(open)
+        .get(cx);
+}
+
+#[test]
+fn components_built_by_event_callbacks_are_rendered_right_away() {
+    let dialog = Rc::new(RefCell::new(None));
+    let harness = TestHarness::new::<Opener>(dialog.clone());
+
+    harness.click("open");
+    let dialog = dialog.borrow();
+    let dialog = dialog.as_ref().unwrap();
+    assert_eq!(dialog.get_by_id("summary").text(), " 0 false");
+}
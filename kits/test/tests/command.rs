@@ -0,0 +1,97 @@
+use std::time::Duration;
+
+use regui::{component::{Command, CommandAction, Component, FunctionsCache, LiveStateComponent, StateLink}, task};
+use regui_test::prelude::*;
+use controls::{Button, Label, VerticalBox, Window};
+
+fn eval<F: ComponentFunction>(cache: &FunctionsCache, props: F::Props) -> F::Out {
+    cache.eval_live::<LiveStateComponent<FunctionComponent<F>>, F::Out>(props)
+}
+
+#[derive(Debug, PartialEq)]
+enum Message {
+    Search,
+    Results(Vec<String>),
+    HideNotice,
+}
+
+#[derive(Default)]
+struct Search {
+    results: Option<Vec<String>>,
+    notice: bool,
+}
+
+impl Component for Search {
+    type Props = ();
+    type Out = ();
+    type Message = Message;
+
+    fn build(_props: (), _link: StateLink<Self>) -> Self {
+        Self::default()
+    }
+
+    fn on_message(&mut self, message: Message, _link: StateLink<Self>) -> Command<Message> {
+        match message {
+            Message::Search => {
+                self.results = None;
+                Command::future(async {
+                    task::sleep(Duration::from_millis(100)).await;
+                    Message::Results(vec!["regui".into(), "iui".into()])
+                })
+            }
+            Message::Results(results) => {
+                self.results = Some(results);
+                self.notice = true;
+                Command::delay(Duration::from_secs(1), Message::HideNotice)
+            }
+            Message::HideNotice => {
+                self.notice = false;
+                Command::none()
+            }
+        }
+    }
+
+    fn view(&self, link: StateLink<Self>, cache: &FunctionsCache) {
+        let search = eval::<Button>(cache, Button::builder()
+            .id("search")
+            .on_click(move || link.send_message(Message::Search)));
+        let results = eval::<Label>(cache, Label::builder()
+            .id("results")
+            .text(&self.results.as_ref().map_or("searching".into(), |results| results.join(", "))));
+        let notice = eval::<Label>(cache, Label::builder()
+            .id("notice")
+            .text(if self.notice { "done" } else { "" }));
+        let vbox = eval::<VerticalBox>(cache, VerticalBox::builder()
+            .child(search)
+            .child(results)
+            .child(notice));
+        eval::<Window>(cache, Window::builder().child(vbox));
+    }
+}
+
+#[test]
+fn commands_can_be_inspected() {
+    let mut search = Search::build((), StateLink::dangling());
+
+    let command = search.on_message(Message::Search, StateLink::dangling());
+    assert!(matches!(command.actions(), [CommandAction::Future(_)]));
+
+    let command = search.on_message(Message::Results(vec![]), StateLink::dangling());
+    assert!(matches!(command.actions(), [CommandAction::Delay(_, Message::HideNotice)]));
+}
+
+#[test]
+fn commands_send_their_messages_back() {
+    let harness = TestHarness::new_component::<Search>(());
+
+    harness.click("search");
+    harness.tick();
+    assert_eq!(harness.get_by_id("results").text(), "searching");
+
+    harness.advance(Duration::from_millis(100));
+    assert_eq!(harness.get_by_id("results").text(), "regui, iui");
+    assert_eq!(harness.get_by_id("notice").text(), "done");
+
+    harness.advance(Duration::from_secs(1));
+    assert_eq!(harness.get_by_id("notice").text(), "");
+}
@@ -0,0 +1,81 @@
+use std::cell::Cell;
+
+use regui::{self as ui, utils::PtrEqRc};
+use regui_test::prelude::*;
+use controls::{Button, Label, VerticalBox, Window};
+
+#[derive(Default)]
+pub struct GreetingProps {
+    name: String,
+}
+
+impl GreetingProps {
+    fn name(mut self, name: &str) -> Self {
+        self.name = name.into();
+        self
+    }
+}
+
+/// Greets someone.
+#[component]
+pub fn greeting(props: &GreetingProps, cx: &mut Cx) -> Node {
+    Label::builder()
+        .id("greeting")
+        .text(&format!("Hello {}", props.name))
+        .get(cx)
+}
+
+#[component(memo, Counted)]
+fn counted_label(calls: &PtrEqRc<Cell<usize>>, cx: &mut Cx) -> Node {
+    calls.set(calls.get() + 1);
+    Label::builder()
+        .id("counted")
+        .text(&calls.get().to_string())
+        .get(cx)
+}
+
+/// The path of `regui` given to the macro, here through a rename.
+#[component(crate = ui, Farewell)]
+fn farewell_label(name: &String, cx: &mut Cx) -> Node {
+    Label::builder()
+        .id("farewell")
+        .text(&format!("Bye {}", name))
+        .get(cx)
+}
+
+#[component]
+fn app(calls: &PtrEqRc<Cell<usize>>, cx: &mut Cx) {
+    let name = cx.use_state(|| "Ada".to_string());
+
+    let greeting = Greeting::eval(cx, Greeting::default_props().name(&name.get()));
+    let counted = Counted::eval(cx, calls.clone());
+    let farewell = Farewell::eval(cx, name.get());
+    let rename = Button::builder()
+        .id("rename")
+        .on_click(move || name.set("Grace".into()))
+        .get(cx);
+    let vbox = VerticalBox::builder()
+        .child(greeting)
+        .child(counted)
+        .child(farewell)
+        .child(rename)
+        .get(cx);
+
+    Window::builder()
+        .child(vbox)
+        .get(cx);
+}
+
+#[test]
+fn attribute_declares_components() {
+    let calls = PtrEqRc::new(Cell::new(0));
+    let harness = TestHarness::new::<App>(calls.clone());
+    assert_eq!(harness.get_by_id("greeting").text(), "Hello Ada");
+    assert_eq!(harness.get_by_id("farewell").text(), "Bye Ada");
+    assert_eq!(calls.get(), 1);
+
+    harness.click("rename");
+    assert_eq!(harness.get_by_id("greeting").text(), "Hello Grace");
+    // `memo` skips the equal props
+    assert_eq!(calls.get(), 1);
+}
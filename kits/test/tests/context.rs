@@ -0,0 +1,138 @@
+use std::cell::Cell;
+
+use regui::utils::PtrEqRc;
+use regui_test::prelude::*;
+use controls::{Button, Label, VerticalBox, Window};
+
+#[derive(Clone, PartialEq)]
+struct Theme {
+    name: String,
+}
+
+decl_function_component!(ThemedLabel themed_label(&'static str) -> Node);
+
+fn themed_label(id: &&'static str, cx: &mut Cx) -> Node {
+    let theme = cx.use_context::<Theme>();
+    Label::builder()
+        .id(id)
+        .text(&theme.map_or("no theme".to_string(), |theme| theme.name))
+        .get(cx)
+}
+
+decl_function_component!(Panel panel(()) -> Node);
+
+/// Provides no context, only passes it through.
+fn panel(_props: &(), cx: &mut Cx) -> Node {
+    let label = ThemedLabel::eval(cx, "label");
+    VerticalBox::builder()
+        .child(label)
+        .get(cx)
+}
+
+decl_function_component!(Nested nested(()) -> Node);
+
+fn nested(_props: &(), cx: &mut Cx) -> Node {
+    cx.provide_context(Theme { name: "nested".into() });
+    ThemedLabel::eval(cx, "nested")
+}
+
+decl_function_component!(App app(()) -> ());
+
+fn app(_props: &(), cx: &mut Cx) {
+    let dark = cx.use_state(|| false);
+    cx.provide_context(Theme { name: if dark.get() { "dark" } else { "light" }.into() });
+
+    let toggle = Button::builder()
+        .id("toggle")
+        .on_click(move || dark.set(!dark.get()))
+        .get(cx);
+    let panel = Panel::eval(cx, ());
+    let nested = Nested::eval(cx, ());
+
+    let vbox = VerticalBox::builder()
+        .child(toggle)
+        .child(panel)
+        .child(nested)
+        .get(cx);
+
+    Window::builder()
+        .child(vbox)
+        .get(cx);
+}
+
+#[test]
+fn descendants_see_the_provided_value() {
+    let harness = TestHarness::new::<App>(());
+    assert_eq!(harness.get_by_id("label").text(), "light");
+
+    harness.click("toggle");
+    assert_eq!(harness.get_by_id("label").text(), "dark");
+}
+
+#[test]
+fn closer_providers_override_the_value() {
+    let harness = TestHarness::new::<App>(());
+    assert_eq!(harness.get_by_id("nested").text(), "nested");
+
+    harness.click("toggle");
+    assert_eq!(harness.get_by_id("nested").text(), "nested");
+}
+
+decl_function_component!(Orphan orphan(()) -> ());
+
+fn orphan(_props: &(), cx: &mut Cx) {
+    let label = ThemedLabel::eval(cx, "label");
+    Window::builder()
+        .child(label)
+        .get(cx);
+}
+
+#[test]
+fn missing_contexts_are_none() {
+    let harness = TestHarness::new::<Orphan>(());
+    assert_eq!(harness.get_by_id("label").text(), "no theme");
+}
+
+decl_function_component!(CountingLabel counting_label(PtrEqRc<Cell<usize>>) -> Node);
+
+fn counting_label(renders: &PtrEqRc<Cell<usize>>, cx: &mut Cx) -> Node {
+    renders.set(renders.get() + 1);
+    let theme = cx.use_context::<Theme>().unwrap();
+    Label::builder()
+        .id("counting")
+        .text(&theme.name)
+        .get(cx)
+}
+
+decl_function_component!(Provider provider(PtrEqRc<Cell<usize>>) -> ());
+
+fn provider(renders: &PtrEqRc<Cell<usize>>, cx: &mut Cx) {
+    let dark = cx.use_state(|| false);
+    cx.provide_context(Theme { name: if dark.get() { "dark" } else { "light" }.into() });
+
+    let toggle = Button::builder()
+        .id("toggle")
+        .on_click(move || dark.set(!dark.get()))
+        .get(cx);
+    let label = CountingLabel::eval(cx, renders.clone());
+
+    let vbox = VerticalBox::builder()
+        .child(toggle)
+        .child(label)
+        .get(cx);
+
+    Window::builder()
+        .child(vbox)
+        .get(cx);
+}
+
+#[test]
+fn consumers_rendered_by_the_provider_are_not_rendered_again() {
+    let renders = PtrEqRc::new(Cell::new(0));
+    let harness = TestHarness::new::<Provider>(renders.clone());
+    let initial = renders.get();
+
+    harness.click("toggle");
+    assert_eq!(harness.get_by_id("counting").text(), "dark");
+    assert_eq!(renders.get(), initial + 1);
+}
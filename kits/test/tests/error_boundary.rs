@@ -0,0 +1,157 @@
+use std::cell::Cell;
+
+use regui::{component::StateManager, function_component::{ErrorBoundary, ErrorBoundaryProps, TryComponentFunction}, utils::PtrEqRc};
+use regui_test::prelude::*;
+use controls::{Button, Label, VerticalBox, Window};
+
+decl_function_component!(Fragile fragile(PtrEqRc<Cell<bool>>) -> Node);
+
+/// Panics on its first view if `fail` is set, or once its button is clicked.
+fn fragile(fail: &PtrEqRc<Cell<bool>>, cx: &mut Cx) -> Node {
+    let broken = cx.use_state(|| false);
+    if fail.get() || broken.get() {
+        panic!("fragile is broken");
+    }
+    Button::builder()
+        .id("break")
+        .on_click(move || broken.set(true))
+        .get(cx)
+}
+
+struct Parse;
+
+impl TryComponentFunction for Parse {
+    type Props = &'static str;
+    type Out = Node;
+    type Error = std::num::ParseIntError;
+    fn try_call(text: &&'static str, cx: &mut Cx) -> Result<Node, Self::Error> {
+        let value: i32 = text.parse()?;
+        Ok(Label::builder()
+            .id("parsed")
+            .text(&value.to_string())
+            .get(cx))
+    }
+}
+
+fn retry_button(error: &regui::component::ComponentError, retry: regui::function_component::Retry, cx: &mut Cx) -> Node {
+    Button::builder()
+        .id("retry")
+        .text(&error.to_string())
+        .on_click(move || retry.retry())
+        .get(cx)
+}
+
+#[derive(Clone, PartialEq)]
+struct AppProps {
+    fail: PtrEqRc<Cell<bool>>,
+    text: &'static str,
+}
+
+decl_function_component!(App app(AppProps) -> ());
+
+fn app(props: &AppProps, cx: &mut Cx) {
+    let fail = props.fail.clone();
+    let fragile = ErrorBoundary::eval(cx, ErrorBoundaryProps::new(
+        move |cx| Fragile::eval(cx, fail.clone()),
+        retry_button,
+    ));
+    let text = props.text;
+    let parsed = ErrorBoundary::eval(cx, ErrorBoundaryProps::new(
+        move |cx| Parse::eval(cx, text),
+        |error, _retry, cx| Label::builder()
+            .id("parse-error")
+            .text(&error.to_string())
+            .get(cx),
+    ));
+    let vbox = VerticalBox::builder()
+        .child(fragile)
+        .child(parsed)
+        .get(cx);
+
+    Window::builder()
+        .child(vbox)
+        .get(cx);
+}
+
+fn props(fail: bool, text: &'static str) -> AppProps {
+    AppProps {
+        fail: PtrEqRc::new(Cell::new(fail)),
+        text,
+    }
+}
+
+#[test]
+fn panics_on_the_first_view_render_the_fallback() {
+    let props = props(true, "1");
+    let harness = TestHarness::new::<App>(props.clone());
+
+    let retry = harness.get_by_id("retry");
+    assert!(retry.text().contains("Fragile"), "{}", retry.text());
+    assert!(retry.text().contains("panicked: fragile is broken"), "{}", retry.text());
+    // the other boundary is not affected
+    assert_eq!(harness.get_by_id("parsed").text(), "1");
+
+    props.fail.set(false);
+    harness.click("retry");
+    assert!(harness.find_by_id("retry").is_none());
+    assert!(harness.find_by_id("break").is_some());
+}
+
+#[test]
+fn panics_after_an_update_render_the_fallback() {
+    let harness = TestHarness::new::<App>(props(false, "1"));
+
+    harness.click("break");
+    assert!(harness.find_by_id("break").is_none());
+    assert!(harness.get_by_id("retry").text().contains("fragile is broken"));
+
+    // the broken component has been dropped, retrying builds it again
+    harness.click("retry");
+    assert!(harness.find_by_id("break").is_some());
+}
+
+#[test]
+fn errors_render_the_fallback() {
+    let harness = TestHarness::new::<App>(props(false, "one"));
+
+    let error = harness.get_by_id("parse-error").text();
+    assert!(error.contains("Parse` failed: invalid digit"), "{}", error);
+    assert!(harness.find_by_id("break").is_some());
+}
+
+decl_function_component!(Unguarded unguarded(()) -> ());
+
+fn unguarded(_props: &(), cx: &mut Cx) {
+    let parsed = Parse::eval(cx, "one");
+    Window::builder()
+        .child(parsed)
+        .get(cx);
+}
+
+#[test]
+#[should_panic(expected = "failed: invalid digit")]
+fn errors_without_boundary_panic() {
+    TestHarness::new::<Unguarded>(());
+}
+
+#[test]
+fn states_render_again_after_a_panicking_update() {
+    let manager = StateManager::new(0);
+    let rendered = PtrEqRc::new(Cell::new(0));
+    manager.set_builder({
+        let rendered = rendered.clone();
+        move |count: &i32, _| {
+            assert!(*count != 1, "the builder panics");
+            rendered.set(*count);
+        }
+    });
+    let link = manager.link();
+
+    let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| link.send_update(|count| *count = 1)));
+    assert!(panic.is_err());
+    let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| link.send_update(|_| panic!("the update panics"))));
+    assert!(panic.is_err());
+
+    link.send_update(|count| *count = 2);
+    assert_eq!(rendered.get(), 2);
+}
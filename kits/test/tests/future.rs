@@ -0,0 +1,107 @@
+use std::{cell::Cell, time::Duration};
+
+use regui::{task::{self, Resource}, utils::PtrEqRc};
+use regui_test::prelude::*;
+use controls::{Button, Label, VerticalBox, Window};
+
+
+#[derive(Clone, PartialEq)]
+struct ProfileProps {
+    user: usize,
+    completed: PtrEqRc<Cell<usize>>,
+}
+
+decl_function_component!(Profile profile(ProfileProps) -> Node);
+
+fn profile(props: &ProfileProps, cx: &mut Cx) -> Node {
+    let user = props.user;
+    let completed = props.completed.clone();
+    let name = cx.use_async_resource(user, async move {
+        task::sleep(Duration::from_millis(100)).await;
+        completed.set(completed.get() + 1);
+        if user == 0 {
+            Err("no user".to_string())
+        } else {
+            Ok(format!("user {}", user))
+        }
+    });
+
+    let text = match name {
+        Resource::Pending => "loading".to_string(),
+        Resource::Ready(name) => name,
+        Resource::Error(error) => format!("error: {}", error),
+    };
+    Label::builder()
+        .id("profile")
+        .text(&text)
+        .get(cx)
+}
+
+decl_function_component!(App app(PtrEqRc<Cell<usize>>) -> ());
+
+fn app(completed: &PtrEqRc<Cell<usize>>, cx: &mut Cx) {
+    let user = cx.use_state(|| 1);
+    let visible = cx.use_state(|| true);
+
+    let next = Button::builder()
+        .id("next")
+        .on_click({
+            let user = user.clone();
+            move || user.set((user.get() + 1) % 3)
+        })
+        .get(cx);
+    let hide = Button::builder()
+        .id("hide")
+        .on_click({
+            let visible = visible.clone();
+            move || visible.set(false)
+        })
+        .get(cx);
+    let mut vbox = VerticalBox::builder()
+        .child(next)
+        .child(hide);
+    if visible.get() {
+        vbox = vbox.child(Profile::eval(cx, ProfileProps { user: user.get(), completed: completed.clone() }));
+    }
+    let vbox = vbox.get(cx);
+
+    Window::builder()
+        .child(vbox)
+        .get(cx);
+}
+
+fn completed() -> PtrEqRc<Cell<usize>> {
+    PtrEqRc::new(Cell::new(0))
+}
+
+#[test]
+fn futures_render_their_result() {
+    let completed = completed();
+    let harness = TestHarness::new::<App>(completed.clone());
+    harness.tick();
+    assert_eq!(harness.get_by_id("profile").text(), "loading");
+
+    harness.advance(Duration::from_millis(100));
+    assert_eq!(harness.get_by_id("profile").text(), "user 1");
+
+    harness.click("next");
+    assert_eq!(harness.get_by_id("profile").text(), "loading");
+    harness.tick();
+    harness.click("next");
+    harness.tick();
+    harness.advance(Duration::from_millis(100));
+    assert_eq!(harness.get_by_id("profile").text(), "error: no user");
+    // the request for the second user has been aborted
+    assert_eq!(completed.get(), 2);
+}
+
+#[test]
+fn futures_are_aborted_with_the_component() {
+    let completed = completed();
+    let harness = TestHarness::new::<App>(completed.clone());
+    harness.tick();
+
+    harness.click("hide");
+    harness.advance(Duration::from_millis(100));
+    assert_eq!(completed.get(), 0);
+}
@@ -0,0 +1,188 @@
+use regui_test::prelude::*;
+use controls::{Button, Entry, HorizontalBox, Label, VerticalBox, Window};
+
+decl_function_component!(Counter counter(()) -> ());
+
+fn counter(_props: &(), cx: &mut Cx) {
+    let count = cx.use_state(|| 0);
+
+    let label = Label::builder()
+        .id("count")
+        .text(&count.get().to_string())
+        .get(cx);
+
+    let increment = Button::builder()
+        .id("increment")
+        .text("Increment")
+        .on_click({
+            let count = count.clone();
+            move || count.set(count.get() + 1)
+        })
+        .get(cx);
+
+    let reset = Button::builder()
+        .id("reset")
+        .text("Reset")
+        .enabled(count.get() > 0)
+        .on_click({
+            let count = count.clone();
+            move || count.set(0)
+        })
+        .get(cx);
+
+    let buttons = HorizontalBox::builder()
+        .child(increment)
+        .child(reset)
+        .get(cx);
+
+    let vbox = VerticalBox::builder()
+        .child(label)
+        .child(buttons)
+        .get(cx);
+
+    Window::builder()
+        .title("Counter")
+        .child(vbox)
+        .get(cx);
+}
+
+#[test]
+fn renders_the_initial_tree() {
+    let harness = TestHarness::new::<Counter>(());
+
+    assert_eq!(harness.tree(), concat!(
+        "Window \"Counter\"\n",
+        "  VerticalBox \"\"\n",
+        "    Label#count \"0\"\n",
+        "    HorizontalBox \"\"\n",
+        "      Button#increment \"Increment\"\n",
+        "      Button#reset \"Reset\" (disabled)\n",
+    ));
+}
+
+#[test]
+fn clicks_rerender_the_tree() {
+    let harness = TestHarness::new::<Counter>(());
+
+    harness.click("increment");
+    harness.click("increment");
+    assert_eq!(harness.get_by_id("count").text(), "2");
+    assert!(harness.get_by_id("reset").enabled());
+
+    harness.click("reset");
+    assert_eq!(harness.get_by_id("count").text(), "0");
+    assert!(!harness.get_by_id("reset").enabled());
+}
+
+#[test]
+#[should_panic(expected = "disabled")]
+fn disabled_buttons_cannot_be_clicked() {
+    let harness = TestHarness::new::<Counter>(());
+    harness.click("reset");
+}
+
+#[test]
+fn windows_close_with_the_harness() {
+    let harness = TestHarness::new::<Counter>(());
+    assert_eq!(harness.windows().len(), 1);
+    drop(harness);
+
+    let harness = TestHarness::new::<Counter>(());
+    assert_eq!(harness.window().text(), "Counter");
+}
+
+decl_function_component!(Greeter greeter(()) -> ());
+
+fn greeter(_props: &(), cx: &mut Cx) {
+    let name = cx.use_state(String::new);
+
+    let entry = Entry::builder()
+        .id("name")
+        .on_changed({
+            let name = name.clone();
+            move |text| name.set(text)
+        })
+        .get(cx);
+
+    let greeting = Label::builder()
+        .id("greeting")
+        .text(&format!("Hello {}!", name.get()))
+        .get(cx);
+
+    let vbox = VerticalBox::builder()
+        .child(entry)
+        .child(greeting)
+        .get(cx);
+
+    Window::builder()
+        .title("Greeter")
+        .child(vbox)
+        .get(cx);
+}
+
+#[test]
+fn input_updates_the_state() {
+    let harness = TestHarness::new::<Greeter>(());
+
+    harness.input("name", "regui");
+    assert_eq!(harness.get_by_id("name").text(), "regui");
+    assert_eq!(harness.get_by_id("greeting").text(), "Hello regui!");
+}
+
+decl_function_component!(Item item(String) -> Node);
+
+/// An item with its own state, to check that it follows its key.
+fn item(name: &String, cx: &mut Cx) -> Node {
+    let clicks = cx.use_state(|| 0);
+    Button::builder()
+        .id(name)
+        .text(&format!("{} {}", name, clicks.get()))
+        .on_click({
+            let clicks = clicks.clone();
+            move || clicks.set(clicks.get() + 1)
+        })
+        .get(cx)
+}
+
+decl_function_component!(List list(()) -> ());
+
+fn list(_props: &(), cx: &mut Cx) {
+    let items = cx.use_state(|| vec!["a".to_string(), "b".to_string()]);
+
+    let add = Button::builder()
+        .id("add")
+        .on_click({
+            let items = items.clone();
+            move || {
+                let mut new_items = items.get();
+                new_items.insert(0, "c".into());
+                items.set(new_items);
+            }
+        })
+        .get(cx);
+
+    let rows: Vec<Node> = items.get().into_iter()
+        .map(|name| Item::eval_keyed(cx, name.clone(), name))
+        .collect();
+
+    let vbox = VerticalBox::builder()
+        .child(add)
+        .children(rows)
+        .get(cx);
+
+    Window::builder()
+        .child(vbox)
+        .get(cx);
+}
+
+#[test]
+fn keyed_children_keep_their_state() {
+    let harness = TestHarness::new::<List>(());
+
+    harness.click("b");
+    harness.click("add");
+
+    assert_eq!(harness.get_by_id("c").text(), "c 0");
+    assert_eq!(harness.get_by_id("a").text(), "a 0");
+    assert_eq!(harness.get_by_id("b").text(), "b 1");
+}
@@ -0,0 +1,170 @@
+use std::{cell::RefCell, rc::Rc};
+
+use regui::component::{batch, Command, Component, FunctionsCache, HistoryStateManager, StateLink};
+
+/// A document edited with messages.
+#[derive(Clone, Default)]
+struct Document {
+    lines: Vec<String>,
+}
+
+enum Edit {
+    Append(String),
+    Pop,
+}
+
+impl Component for Document {
+    type Props = ();
+    type Out = ();
+    type Message = Edit;
+
+    fn build(_props: (), _link: StateLink<Self>) -> Self {
+        Self::default()
+    }
+
+    fn on_message(&mut self, edit: Edit, _link: StateLink<Self>) -> Command<Edit> {
+        match edit {
+            Edit::Append(line) => self.lines.push(line),
+            Edit::Pop => {
+                self.lines.pop();
+            }
+        }
+        Command::none()
+    }
+
+    fn view(&self, _link: StateLink<Self>, _cache: &FunctionsCache) {}
+}
+
+/// The lines of every view.
+type Renders = Rc<RefCell<Vec<Vec<String>>>>;
+
+/// A manager whose builder records the rendered lines.
+fn manager() -> (HistoryStateManager<Document>, Renders) {
+    let manager = HistoryStateManager::new(Document::default());
+    let renders = Rc::new(RefCell::new(Vec::new()));
+    manager.set_builder({
+        let renders = renders.clone();
+        move |document, _| renders.borrow_mut().push(document.lines.clone())
+    });
+    (manager, renders)
+}
+
+fn lines(manager: &HistoryStateManager<Document>) -> Vec<String> {
+    manager.on_state(|document| document.lines.clone())
+}
+
+#[test]
+fn messages_are_undone_and_redone() {
+    let (manager, renders) = manager();
+    let (link, history) = (manager.link(), manager.history());
+    assert!(!history.can_undo());
+
+    link.send_message(Edit::Append("a".into()));
+    link.send_message(Edit::Append("b".into()));
+    link.send_message(Edit::Pop);
+    assert_eq!(lines(&manager), ["a"]);
+
+    history.undo();
+    assert_eq!(lines(&manager), ["a", "b"]);
+    assert_eq!(renders.borrow().last().unwrap(), &["a", "b"]);
+    history.undo();
+    history.undo();
+    assert_eq!(lines(&manager), Vec::<String>::new());
+    assert!(!history.can_undo());
+
+    // nothing to undo, nothing rendered
+    let rendered = renders.borrow().len();
+    history.undo();
+    assert_eq!(renders.borrow().len(), rendered);
+
+    history.redo();
+    history.redo();
+    assert_eq!(lines(&manager), ["a", "b"]);
+    assert!(history.can_redo());
+
+    // a new entry forgets what can be redone
+    link.send_message(Edit::Append("c".into()));
+    assert!(!history.can_redo());
+    history.undo();
+    assert_eq!(lines(&manager), ["a", "b"]);
+}
+
+#[test]
+fn transactions_make_a_single_entry() {
+    let (manager, _renders) = manager();
+    let (link, history) = (manager.link(), manager.history());
+
+    link.send_message(Edit::Append("title".into()));
+    history.transaction(|| {
+        link.send_message(Edit::Append("first".into()));
+        history.transaction(|| link.send_message(Edit::Append("second".into())));
+        link.send_update(|document| document.lines[0] = "renamed".into());
+    });
+    assert_eq!(lines(&manager), ["renamed", "first", "second"]);
+
+    history.undo();
+    assert_eq!(lines(&manager), ["title"]);
+    history.redo();
+    assert_eq!(lines(&manager), ["renamed", "first", "second"]);
+}
+
+#[test]
+fn the_oldest_entries_are_forgotten() {
+    let (manager, _renders) = manager();
+    let manager = manager.with_max_depth(2);
+    let (link, history) = (manager.link(), manager.history());
+
+    for line in ["a", "b", "c"] {
+        link.send_message(Edit::Append(line.into()));
+    }
+    history.undo();
+    history.undo();
+    history.undo();
+    assert_eq!(lines(&manager), ["a"]);
+}
+
+#[test]
+fn time_travel_in_a_batch_renders_once() {
+    let (manager, renders) = manager();
+    let (link, history) = (manager.link(), manager.history());
+    link.send_message(Edit::Append("a".into()));
+    link.send_message(Edit::Append("b".into()));
+
+    let rendered = renders.borrow().len();
+    batch(|| {
+        history.undo();
+        history.undo();
+    });
+    assert_eq!(renders.borrow().len(), rendered + 1);
+    assert_eq!(renders.borrow().last().unwrap(), &Vec::<String>::new());
+}
+
+#[test]
+fn updates_that_do_not_render_are_not_recorded() {
+    let (manager, _renders) = manager();
+    let (link, history) = (manager.link(), manager.history());
+
+    link.send_message(Edit::Append("a".into()));
+    link.send_update_if(|_| false);
+    history.undo();
+    assert_eq!(lines(&manager), Vec::<String>::new());
+    assert!(!history.can_undo());
+}
+
+#[test]
+fn panicking_transactions_are_closed() {
+    let (manager, _renders) = manager();
+    let (link, history) = (manager.link(), manager.history());
+
+    let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| history.transaction(|| {
+        link.send_message(Edit::Append("a".into()));
+        panic!("the transaction panics");
+    })));
+    assert!(panic.is_err());
+
+    // separate entries again
+    link.send_message(Edit::Append("b".into()));
+    link.send_message(Edit::Append("c".into()));
+    history.undo();
+    assert_eq!(lines(&manager), ["a", "b"]);
+}
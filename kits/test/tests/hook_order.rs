@@ -0,0 +1,70 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use regui_test::prelude::*;
+use controls::{Button, Window};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mistake {
+    ChangedType,
+    Missing,
+    Added,
+}
+
+decl_function_component!(Conditional conditional(Mistake) -> ());
+
+/// Calls its hooks differently once the button is clicked.
+fn conditional(mistake: &Mistake, cx: &mut Cx) {
+    let clicked = cx.use_state(|| false);
+    match (*mistake, clicked.get()) {
+        (Mistake::ChangedType, false) | (Mistake::Missing, false) => {
+            cx.use_state(|| 0);
+        }
+        (Mistake::ChangedType, true) => {
+            cx.use_ref(|| 0);
+        }
+        (Mistake::Added, true) => {
+            cx.use_memo((), |_| 0);
+        }
+        _ => {}
+    }
+
+    let button = Button::builder()
+        .id("click")
+        .on_click(move || clicked.set(true))
+        .get(cx);
+
+    Window::builder()
+        .child(button)
+        .get(cx);
+}
+
+fn panic_message(mistake: Mistake) -> String {
+    let harness = TestHarness::new::<Conditional>(mistake);
+    let payload = panic::catch_unwind(AssertUnwindSafe(|| harness.click("click")))
+        .expect_err("the hooks have changed");
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(_) => panic!("the message is formatted"),
+    }
+}
+
+#[test]
+fn changed_hooks_are_reported() {
+    let message = panic_message(Mistake::ChangedType);
+    assert!(message.contains("hook #1 of `hook_order::Conditional` changed between views"), "{}", message);
+    assert!(message.contains("it was `UseReducer` called at kits/test/tests/hook_order.rs:20"), "{}", message);
+    assert!(message.contains("it is now `UseRef` called at kits/test/tests/hook_order.rs:23"), "{}", message);
+}
+
+#[test]
+fn missing_hooks_are_reported() {
+    let message = panic_message(Mistake::Missing);
+    assert!(message.contains("`hook_order::Conditional` called 1 hooks, the previous views called 2"), "{}", message);
+    assert!(message.contains("`UseReducer` called at kits/test/tests/hook_order.rs:20"), "{}", message);
+}
+
+#[test]
+fn added_hooks_are_reported() {
+    let message = panic_message(Mistake::Added);
+    assert!(message.contains("`hook_order::Conditional` called a new hook `UseMemo` at kits/test/tests/hook_order.rs:26"), "{}", message);
+}
@@ -0,0 +1,348 @@
+use std::{rc::Rc, cell::{Cell, RefCell}};
+
+use regui_test::prelude::*;
+use controls::{Button, Label, VerticalBox, Window};
+
+type Log = Rc<RefCell<Vec<String>>>;
+
+#[derive(Clone)]
+struct ChildProps {
+    log: Log,
+    value: i32,
+}
+
+decl_function_component!(Child child(ChildProps) -> Node);
+
+fn child(props: &ChildProps, cx: &mut Cx) -> Node {
+    cx.use_effect(props.value, {
+        let log = props.log.clone();
+        move |value| {
+            log.borrow_mut().push(format!("run {}", value));
+            let value = *value;
+            move || log.borrow_mut().push(format!("cleanup {}", value))
+        }
+    });
+
+    Label::builder()
+        .id("child")
+        .text(&props.value.to_string())
+        .get(cx)
+}
+
+decl_function_component!(Parent parent(Log) -> ());
+
+fn parent(log: &Log, cx: &mut Cx) {
+    let value = cx.use_state(|| 0);
+    let visible = cx.use_state(|| true);
+
+    let increment = Button::builder()
+        .id("increment")
+        .on_click({
+            let value = value.clone();
+            move || value.set(value.get() + 1)
+        })
+        .get(cx);
+    let rerender = Button::builder()
+        .id("rerender")
+        .on_click({
+            let value = value.clone();
+            move || value.set(value.get())
+        })
+        .get(cx);
+    let hide = Button::builder()
+        .id("hide")
+        .on_click({
+            let visible = visible.clone();
+            move || visible.set(false)
+        })
+        .get(cx);
+
+    let mut vbox = VerticalBox::builder()
+        .child(increment)
+        .child(rerender)
+        .child(hide);
+    if visible.get() {
+        vbox = vbox.child(Child::eval(cx, ChildProps { log: log.clone(), value: value.get() }));
+    }
+    let vbox = vbox.get(cx);
+
+    Window::builder()
+        .child(vbox)
+        .get(cx);
+}
+
+fn take(log: &Log) -> Vec<String> {
+    std::mem::take(&mut *log.borrow_mut())
+}
+
+#[test]
+fn effects_run_when_deps_change() {
+    let log = Log::default();
+    let harness = TestHarness::new::<Parent>(log.clone());
+    assert_eq!(take(&log), ["run 0"]);
+
+    harness.click("rerender");
+    assert_eq!(take(&log), Vec::<String>::new());
+
+    harness.click("increment");
+    assert_eq!(take(&log), ["cleanup 0", "run 1"]);
+}
+
+#[test]
+fn effects_are_cleaned_up_on_drop() {
+    let log = Log::default();
+    let harness = TestHarness::new::<Parent>(log.clone());
+    take(&log);
+
+    harness.click("hide");
+    assert_eq!(take(&log), ["cleanup 0"]);
+    assert!(harness.find_by_id("child").is_none());
+}
+
+#[test]
+fn effects_are_cleaned_up_with_the_root() {
+    let log = Log::default();
+    let harness = TestHarness::new::<Parent>(log.clone());
+    take(&log);
+
+    drop(harness);
+    assert_eq!(take(&log), ["cleanup 0"]);
+}
+
+decl_function_component!(Loading loading(()) -> ());
+
+/// Sets the state from an effect, the view has to run again.
+fn loading(_props: &(), cx: &mut Cx) {
+    let loaded = cx.use_state(|| false);
+
+    cx.use_effect((), {
+        let loaded = loaded.clone();
+        move |_| {
+            loaded.set(true);
+            || {}
+        }
+    });
+
+    let label = Label::builder()
+        .id("status")
+        .text(if loaded.get() { "loaded" } else { "loading" })
+        .get(cx);
+
+    Window::builder()
+        .child(label)
+        .get(cx);
+}
+
+#[test]
+fn effects_can_update_the_state() {
+    let harness = TestHarness::new::<Loading>(());
+    assert_eq!(harness.get_by_id("status").text(), "loaded");
+}
+
+enum Action {
+    Increment,
+    Reset,
+}
+
+decl_function_component!(Reducer reducer(()) -> ());
+
+fn reducer(_props: &(), cx: &mut Cx) {
+    let count = cx.use_reducer(|| 0, |count, action| match action {
+        Action::Increment => count + 1,
+        Action::Reset => 0,
+    });
+
+    let label = Label::builder()
+        .id("count")
+        .text(&count.get().to_string())
+        .get(cx);
+    let increment = Button::builder()
+        .id("increment")
+        .on_click({
+            let count = count.clone();
+            move || count.dispatch(Action::Increment)
+        })
+        .get(cx);
+    let reset = Button::builder()
+        .id("reset")
+        .on_click(move || count.dispatch(Action::Reset))
+        .get(cx);
+
+    let vbox = VerticalBox::builder()
+        .child(label)
+        .child(increment)
+        .child(reset)
+        .get(cx);
+
+    Window::builder()
+        .child(vbox)
+        .get(cx);
+}
+
+#[test]
+fn reducers_handle_actions() {
+    let harness = TestHarness::new::<Reducer>(());
+
+    harness.click("increment");
+    harness.click("increment");
+    assert_eq!(harness.get_by_id("count").text(), "2");
+
+    harness.click("reset");
+    assert_eq!(harness.get_by_id("count").text(), "0");
+}
+
+/// Counts the views of the component.
+struct UseViewCount;
+
+impl Hook for UseViewCount {
+    type Slot = usize;
+    type Output = usize;
+    fn init(&mut self, _cx: &mut HookCx) -> usize {
+        0
+    }
+    fn call(self, count: &mut usize, _cx: &mut HookCx) -> usize {
+        *count += 1;
+        *count
+    }
+}
+
+decl_function_component!(Views views(()) -> ());
+
+fn views(_props: &(), cx: &mut Cx) {
+    let views = cx.use_hook(UseViewCount);
+    let rerender = cx.use_hook(UseRerender);
+
+    let button = Button::builder()
+        .id("views")
+        .text(&views.to_string())
+        .on_click(move || rerender.rerender())
+        .get(cx);
+
+    Window::builder()
+        .child(button)
+        .get(cx);
+}
+
+/// Gives access to the link of the component.
+struct UseRerender;
+
+impl Hook for UseRerender {
+    type Slot = ();
+    type Output = HookLink;
+    fn init(&mut self, _cx: &mut HookCx) {}
+    fn call(self, _slot: &mut (), cx: &mut HookCx) -> HookLink {
+        cx.link()
+    }
+}
+
+#[test]
+fn custom_hooks_own_a_slot() {
+    let harness = TestHarness::new::<Views>(());
+    let views = || harness.get_by_id("views").text().parse::<usize>().unwrap();
+    let initial = views();
+
+    harness.click("views");
+    harness.click("views");
+    assert_eq!(views(), initial + 2);
+}
+
+decl_function_component!(Memo memo(Rc<Cell<usize>>) -> ());
+
+fn memo(computations: &Rc<Cell<usize>>, cx: &mut Cx) {
+    let value = cx.use_state(|| 1);
+
+    let squared = cx.use_memo(value.get(), {
+        let computations = computations.clone();
+        move |value| {
+            computations.set(computations.get() + 1);
+            value * value
+        }
+    });
+    let callback = cx.use_callback(value.get(), || {});
+    let first_callback = cx.use_ref(|| callback.clone());
+
+    let label = Label::builder()
+        .id("squared")
+        .text(&squared.to_string())
+        .get(cx);
+    let same_callback = Label::builder()
+        .id("same_callback")
+        .text(&(callback == *first_callback).to_string())
+        .get(cx);
+    let rerender = Button::builder()
+        .id("rerender")
+        .on_click({
+            let value = value.clone();
+            move || value.set(value.get())
+        })
+        .get(cx);
+    let increment = Button::builder()
+        .id("increment")
+        .on_click(move || value.set(value.get() + 1))
+        .get(cx);
+
+    let vbox = VerticalBox::builder()
+        .child(label)
+        .child(same_callback)
+        .child(rerender)
+        .child(increment)
+        .get(cx);
+
+    Window::builder()
+        .child(vbox)
+        .get(cx);
+}
+
+#[test]
+fn memos_are_computed_when_deps_change() {
+    let computations = Rc::new(Cell::new(0));
+    let harness = TestHarness::new::<Memo>(computations.clone());
+    assert_eq!(computations.get(), 1);
+
+    harness.click("rerender");
+    assert_eq!(computations.get(), 1);
+    assert_eq!(harness.get_by_id("same_callback").text(), "true");
+
+    harness.click("increment");
+    assert_eq!(computations.get(), 2);
+    assert_eq!(harness.get_by_id("squared").text(), "4");
+    assert_eq!(harness.get_by_id("same_callback").text(), "false");
+}
+
+decl_function_component!(Setter setter(Log) -> ());
+
+fn setter(log: &Log, cx: &mut Cx) {
+    let value = cx.use_state(|| 0);
+    if value.get() == 0 {
+        value.set(1);
+        // applied once the view returns
+        log.borrow_mut().push(format!("view {}", value.get()));
+    }
+
+    let increment = Button::builder()
+        .id("increment")
+        .on_click({
+            let (value, log) = (value.clone(), log.clone());
+            move || {
+                value.set(value.get() + 1);
+                log.borrow_mut().push(format!("handler {}", value.get()));
+            }
+        })
+        .text(&value.get().to_string())
+        .get(cx);
+
+    Window::builder()
+        .child(increment)
+        .get(cx);
+}
+
+#[test]
+fn states_set_while_rendering_change_after_the_view() {
+    let log = Log::default();
+    let harness = TestHarness::new::<Setter>(log.clone());
+    assert_eq!(harness.get_by_id("increment").text(), "1");
+
+    harness.click("increment");
+    assert_eq!(*log.borrow(), ["view 0", "handler 2"]);
+    assert_eq!(harness.get_by_id("increment").text(), "2");
+}
@@ -0,0 +1,126 @@
+use std::{rc::Rc, cell::RefCell};
+
+use regui::component::{Command, Component, FunctionsCache, LiveStateComponent, StateLink};
+use regui_test::prelude::*;
+use controls::{Button, Label, VerticalBox, Window};
+
+type Log = Rc<RefCell<Vec<String>>>;
+
+fn take(log: &Log) -> Vec<String> {
+    std::mem::take(&mut *log.borrow_mut())
+}
+
+/// Starts "loading" when mounted.
+struct Loader {
+    log: Log,
+    loaded: bool,
+}
+
+impl Component for Loader {
+    type Props = Log;
+    type Out = Node;
+    type Message = ();
+
+    fn build(log: Log, _link: StateLink<Self>) -> Self {
+        Self { log, loaded: false }
+    }
+
+    fn on_message(&mut self, _message: (), _link: StateLink<Self>) -> Command<Self::Message> {
+        self.loaded = true;
+        Command::none()
+    }
+
+    fn on_mount(&mut self, link: StateLink<Self>) {
+        self.log.borrow_mut().push("loader mounted".into());
+        link.send_message(());
+    }
+
+    fn on_unmount(&mut self) {
+        self.log.borrow_mut().push("loader unmounted".into());
+    }
+
+    fn view(&self, _link: StateLink<Self>, cache: &FunctionsCache) -> Node {
+        cache.eval_live::<LiveStateComponent<FunctionComponent<Label>>, Node>(Label::builder()
+            .id("loader")
+            .text(if self.loaded { "loaded" } else { "loading" }))
+    }
+}
+
+decl_function_component!(Timer timer(Log) -> Node);
+
+fn timer(log: &Log, cx: &mut Cx) -> Node {
+    let ticks = cx.use_state(|| 0);
+    cx.use_mount({
+        let log = log.clone();
+        move || log.borrow_mut().push("timer mounted".into())
+    });
+    cx.use_unmount({
+        let log = log.clone();
+        let ticks = ticks.get();
+        move || log.borrow_mut().push(format!("timer unmounted after {} ticks", ticks))
+    });
+
+    Button::builder()
+        .id("tick")
+        .on_click(move || ticks.set(ticks.get() + 1))
+        .get(cx)
+}
+
+decl_function_component!(App app(Log) -> ());
+
+fn app(log: &Log, cx: &mut Cx) {
+    let visible = cx.use_state(|| true);
+
+    let hide = Button::builder()
+        .id("hide")
+        .on_click({
+            let visible = visible.clone();
+            move || visible.set(false)
+        })
+        .get(cx);
+    let mut vbox = VerticalBox::builder().child(hide);
+    if visible.get() {
+        vbox = vbox
+            .child(cx.cache().eval_live::<LiveStateComponent<Loader>, Node>(log.clone()))
+            .child(Timer::eval(cx, log.clone()));
+    }
+    let vbox = vbox.get(cx);
+
+    Window::builder()
+        .child(vbox)
+        .get(cx);
+}
+
+#[test]
+fn components_are_mounted_after_the_first_view() {
+    let log = Log::default();
+    let harness = TestHarness::new::<App>(log.clone());
+    assert_eq!(take(&log), ["loader mounted", "timer mounted"]);
+    // the message sent when mounting has been applied
+    assert_eq!(harness.get_by_id("loader").text(), "loaded");
+
+    harness.click("tick");
+    assert_eq!(take(&log), Vec::<String>::new());
+}
+
+#[test]
+fn components_are_unmounted_when_removed() {
+    let log = Log::default();
+    let harness = TestHarness::new::<App>(log.clone());
+    take(&log);
+
+    harness.click("tick");
+    harness.click("tick");
+    harness.click("hide");
+    assert_eq!(take(&log), ["loader unmounted", "timer unmounted after 2 ticks"]);
+}
+
+#[test]
+fn components_are_unmounted_with_the_root() {
+    let log = Log::default();
+    let harness = TestHarness::new::<App>(log.clone());
+    take(&log);
+
+    drop(harness);
+    assert_eq!(take(&log), ["loader unmounted", "timer unmounted after 0 ticks"]);
+}
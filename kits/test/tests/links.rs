@@ -0,0 +1,112 @@
+use std::{rc::Rc, cell::RefCell};
+
+use regui::component::{Command, Component, FunctionsCache, LiveStateComponent, StateLink, StateManager};
+use regui_test::prelude::*;
+use controls::{Label, Window};
+
+/// Where the countdown registers itself, like a timer would.
+type Timer = Rc<RefCell<Option<StateLink<Countdown>>>>;
+
+enum Message {
+    Start(u32),
+    Tick,
+}
+
+struct Countdown {
+    remaining: u32,
+    ticks: Vec<u32>,
+}
+
+impl Component for Countdown {
+    type Props = Timer;
+    type Out = ();
+    type Message = Message;
+
+    fn build(timer: Timer, link: StateLink<Self>) -> Self {
+        *timer.borrow_mut() = Some(link);
+        Self { remaining: 0, ticks: Vec::new() }
+    }
+
+    fn on_message(&mut self, message: Message, link: StateLink<Self>) -> Command<Self::Message> {
+        match message {
+            Message::Start(from) => self.remaining = from,
+            Message::Tick => self.remaining -= 1,
+        }
+        self.ticks.push(self.remaining);
+        if self.remaining > 0 {
+            link.send_message(Message::Tick);
+        }
+        Command::none()
+    }
+
+    fn view(&self, _link: StateLink<Self>, cache: &FunctionsCache) {
+        let ticks: Vec<_> = self.ticks.iter().map(|tick| tick.to_string()).collect();
+        let label = cache.eval_live::<LiveStateComponent<FunctionComponent<Label>>, Node>(Label::builder()
+            .id("ticks")
+            .text(&ticks.join(" ")));
+        let _window = cache.eval_live::<LiveStateComponent<FunctionComponent<Window>>, Node>(Window::builder().child(label));
+    }
+}
+
+#[test]
+fn components_send_messages_through_their_links() {
+    let timer = Timer::default();
+    let harness = TestHarness::new_component::<Countdown>(timer.clone());
+
+    let link = timer.borrow().clone().expect("registered in build");
+    link.send_message(Message::Start(3));
+    assert_eq!(harness.get_by_id("ticks").text(), "3 2 1 0");
+}
+
+/// Greets the name it is built with, through a message sent from `build`.
+struct Greeting {
+    text: String,
+}
+
+impl Component for Greeting {
+    type Props = &'static str;
+    type Out = ();
+    type Message = &'static str;
+
+    fn build(name: &'static str, link: StateLink<Self>) -> Self {
+        link.send_message(name);
+        Self { text: "hello".into() }
+    }
+
+    fn on_message(&mut self, name: &'static str, _link: StateLink<Self>) -> Command<Self::Message> {
+        self.text = format!("{} {}", self.text, name);
+        Command::none()
+    }
+
+    fn view(&self, _link: StateLink<Self>, cache: &FunctionsCache) {
+        let label = cache.eval_live::<LiveStateComponent<FunctionComponent<Label>>, Node>(Label::builder()
+            .id("greeting")
+            .text(&self.text));
+        let _window = cache.eval_live::<LiveStateComponent<FunctionComponent<Window>>, Node>(Window::builder().child(label));
+    }
+}
+
+#[test]
+fn messages_sent_while_building_are_handled_before_the_first_view() {
+    let harness = TestHarness::new_component::<Greeting>("Ada");
+    assert_eq!(harness.get_by_id("greeting").text(), "hello Ada");
+}
+
+#[test]
+fn updates_sent_while_creating_a_state_are_queued() {
+    let panic = std::panic::catch_unwind(|| StateManager::<Vec<i32>>::new_with_link(|_| panic!("init panics")));
+    assert!(panic.is_err());
+
+    let manager = StateManager::new_with_link(|link| {
+        link.send_update(|values: &mut Vec<i32>| values.push(1));
+        // another state created meanwhile has its own queue
+        let other = StateManager::new_with_link(|other_link| {
+            other_link.send_update(|value: &mut i32| *value += 1);
+            link.send_update(|values| values.push(2));
+            10
+        });
+        assert_eq!(other.on_state(|value| *value), 11);
+        vec![0]
+    });
+    assert_eq!(manager.on_state(Vec::clone), [0, 1, 2]);
+}
@@ -0,0 +1,61 @@
+use std::cell::Cell;
+
+use regui::utils::PtrEqRc;
+use regui_test::prelude::*;
+use controls::{Entry, Label, VerticalBox, Window};
+
+#[derive(PartialEq)]
+struct FieldProps {
+    id: &'static str,
+    value: String,
+    calls: PtrEqRc<Cell<usize>>,
+}
+
+decl_function_component!(memo Field field(FieldProps) -> Node);
+
+fn field(props: &FieldProps, cx: &mut Cx) -> Node {
+    props.calls.set(props.calls.get() + 1);
+    Label::builder()
+        .id(props.id)
+        .text(&props.value)
+        .get(cx)
+}
+
+decl_function_component!(Form form(PtrEqRc<Cell<usize>>) -> ());
+
+fn form(calls: &PtrEqRc<Cell<usize>>, cx: &mut Cx) {
+    let name = cx.use_state(|| "".to_string());
+
+    let entry = Entry::builder()
+        .id("entry")
+        .on_changed({
+            let name = name.clone();
+            move |text| name.set(text)
+        })
+        .get(cx);
+    let echo = Field::eval(cx, FieldProps { id: "echo", value: name.get(), calls: calls.clone() });
+    let fixed = Field::eval(cx, FieldProps { id: "fixed", value: "fixed".into(), calls: calls.clone() });
+
+    let vbox = VerticalBox::builder()
+        .child(entry)
+        .child(echo)
+        .child(fixed)
+        .get(cx);
+
+    Window::builder()
+        .child(vbox)
+        .get(cx);
+}
+
+#[test]
+fn memo_components_skip_equal_props() {
+    let calls = PtrEqRc::new(Cell::new(0));
+    let harness = TestHarness::new::<Form>(calls.clone());
+    assert_eq!(calls.get(), 2);
+
+    // only the field showing the text is called again
+    harness.input("entry", "Ada");
+    assert_eq!(harness.get_by_id("echo").text(), "Ada");
+    assert_eq!(harness.get_by_id("fixed").text(), "fixed");
+    assert_eq!(calls.get(), 3);
+}
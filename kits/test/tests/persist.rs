@@ -0,0 +1,152 @@
+use std::{cell::RefCell, rc::Rc};
+
+use regui::{
+    component::{Command, Component, FunctionsCache, LiveStateComponent, StateLink},
+    persist::{self, FileStorage, Format, MemoryStorage, Persistence, PersistErrorKind, StorageBackend},
+};
+use regui_test::prelude::*;
+use controls::{Button, Label, VerticalBox, Window};
+use serde::{Deserialize, Serialize};
+
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+struct Preferences {
+    zoom: u32,
+    dark: bool,
+}
+
+decl_function_component!(App app(()) -> ());
+
+fn app(_props: &(), cx: &mut Cx) {
+    let preferences = cx.use_persistent_state("preferences", || Preferences { zoom: 100, dark: false });
+
+    let zoom_in = Button::builder()
+        .id("zoom_in")
+        .on_click({
+            let preferences = preferences.clone();
+            move || preferences.set(Preferences { zoom: preferences.get().zoom + 10, ..preferences.get() })
+        })
+        .get(cx);
+    let zoom = Label::builder()
+        .id("zoom")
+        .text(&preferences.get().zoom.to_string())
+        .get(cx);
+    let vbox = VerticalBox::builder()
+        .child(zoom_in)
+        .child(zoom)
+        .get(cx);
+
+    Window::builder()
+        .child(vbox)
+        .get(cx);
+}
+
+fn stored(storage: &MemoryStorage, key: &str) -> Option<Preferences> {
+    storage.get(key).map(|text| Format::Json.deserialize(&text).unwrap())
+}
+
+#[test]
+fn persistent_states_are_saved_and_restored() {
+    let storage = MemoryStorage::new();
+    persist::set_storage_backend(storage.clone());
+
+    let harness = TestHarness::new::<App>(());
+    assert_eq!(harness.get_by_id("zoom").text(), "100");
+    harness.click("zoom_in");
+    assert_eq!(stored(&storage, "preferences"), Some(Preferences { zoom: 110, dark: false }));
+    drop(harness);
+
+    // the next run
+    let harness = TestHarness::new::<App>(());
+    assert_eq!(harness.get_by_id("zoom").text(), "110");
+}
+
+#[test]
+fn values_that_cannot_be_restored_are_replaced() {
+    let storage = MemoryStorage::new();
+    storage.insert("preferences", "{ \"zoom\": \"large\" }");
+    persist::set_storage_backend(storage.clone());
+    let errors = Rc::new(RefCell::new(Vec::new()));
+    persist::set_persist_error_handler({
+        let errors = errors.clone();
+        move |error| errors.borrow_mut().push((error.key.clone(), matches!(error.kind, PersistErrorKind::Deserialize(_))))
+    });
+
+    let harness = TestHarness::new::<App>(());
+    assert_eq!(harness.get_by_id("zoom").text(), "100");
+    assert_eq!(*errors.borrow(), vec![("preferences".to_string(), true)]);
+    assert_eq!(stored(&storage, "preferences"), Some(Preferences { zoom: 100, dark: false }));
+}
+
+/// A component persisting a part of its state.
+struct Counter {
+    count: i32,
+    /// Not persisted.
+    clicks: usize,
+}
+
+impl Component for Counter {
+    type Props = String;
+    type Out = ();
+    type Message = ();
+
+    fn build(_key: String, _link: StateLink<Self>) -> Self {
+        Self { count: 0, clicks: 0 }
+    }
+
+    fn on_message(&mut self, _message: (), _link: StateLink<Self>) -> Command<()> {
+        self.count += 1;
+        self.clicks += 1;
+        Command::none()
+    }
+
+    fn persistence(key: &String) -> Option<Persistence<Self>> {
+        Some(Persistence::new(key.clone(), |counter: &Counter| counter.count, |counter, count| counter.count = count))
+    }
+
+    fn view(&self, link: StateLink<Self>, cache: &FunctionsCache) {
+        let label = cache.eval_live::<LiveStateComponent<FunctionComponent<Label>>, _>(Label::builder()
+            .id("count")
+            .text(&format!("{} ({} clicks)", self.count, self.clicks)));
+        let button = cache.eval_live::<LiveStateComponent<FunctionComponent<Button>>, _>(Button::builder()
+            .id("increment")
+            .on_click(move || link.send_message(())));
+        let vbox = cache.eval_live::<LiveStateComponent<FunctionComponent<VerticalBox>>, _>(VerticalBox::builder()
+            .child(label)
+            .child(button));
+        let _: Node = cache.eval_live::<LiveStateComponent<FunctionComponent<Window>>, _>(Window::builder().child(vbox));
+    }
+}
+
+#[test]
+fn components_are_restored_when_built() {
+    let storage = MemoryStorage::with_format(Format::Ron);
+    storage.insert("counter", "41");
+    persist::set_storage_backend(storage.clone());
+
+    let harness = TestHarness::new_component::<Counter>("counter".to_string());
+    assert_eq!(harness.get_by_id("count").text(), "41 (0 clicks)");
+    harness.click("increment");
+    assert_eq!(harness.get_by_id("count").text(), "42 (1 clicks)");
+    assert_eq!(storage.get("counter").as_deref(), Some("42"));
+}
+
+#[test]
+fn files_are_named_after_the_keys() {
+    let dir = std::env::temp_dir().join(format!("regui-persist-{}", std::process::id()));
+    let storage = FileStorage::new(&dir, Format::Ron);
+    assert_eq!(storage.load("window/main").unwrap(), None);
+
+    storage.save("window/main", "(zoom: 120, dark: true)").unwrap();
+    assert!(dir.join("window%2Fmain.ron").exists());
+    let text = storage.load("window/main").unwrap().unwrap();
+    assert_eq!(Format::Ron.deserialize::<Preferences>(&text), Ok(Preferences { zoom: 120, dark: true }));
+
+    // the keys that used to collide have their own files
+    assert_eq!(storage.load("window_main").unwrap(), None);
+    assert_eq!(storage.load("window:main").unwrap(), None);
+    storage.save("../escaped", "()").unwrap();
+    assert!(dir.join("..%2Fescaped.ron").exists());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
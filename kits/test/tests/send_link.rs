@@ -0,0 +1,101 @@
+use std::{thread, sync::{atomic::{AtomicUsize, Ordering}, mpsc, Arc}};
+
+use regui::component::{drain_sent_updates, set_wake, Command, Component, FunctionsCache, LiveStateComponent, StateLink, StateManager};
+use regui_test::prelude::*;
+use controls::{Button, Label, VerticalBox, Window};
+
+fn eval<F: ComponentFunction>(cache: &FunctionsCache, props: F::Props) -> F::Out {
+    cache.eval_live::<LiveStateComponent<FunctionComponent<F>>, F::Out>(props)
+}
+
+/// Computes a value on a worker thread, the worker waits for `go` before sending it.
+struct Worker {
+    result: Option<u64>,
+    go: Option<mpsc::Receiver<()>>,
+}
+
+impl Component for Worker {
+    type Props = mpsc::Receiver<()>;
+    type Out = ();
+    type Message = u64;
+
+    fn build(go: Self::Props, _link: StateLink<Self>) -> Self {
+        Self { result: None, go: Some(go) }
+    }
+
+    fn on_message(&mut self, result: u64, _link: StateLink<Self>) -> Command<Self::Message> {
+        self.result = Some(result);
+        Command::none()
+    }
+
+    fn view(&self, link: StateLink<Self>, cache: &FunctionsCache) {
+        let label = eval::<Label>(cache, Label::builder()
+            .id("result")
+            .text(&self.result.map_or("working".to_string(), |result| result.to_string())));
+        let start = eval::<Button>(cache, Button::builder()
+            .id("start")
+            .on_click(move || {
+                let send_link = link.send_link();
+                link.send_update(move |worker| {
+                    let go = worker.go.take().expect("started once");
+                    thread::spawn(move || {
+                        go.recv().unwrap();
+                        send_link.send_message((1..=10).sum());
+                    });
+                });
+            }));
+        let vbox = eval::<VerticalBox>(cache, VerticalBox::builder()
+            .child(label)
+            .child(start));
+        eval::<Window>(cache, Window::builder().child(vbox));
+    }
+}
+
+#[test]
+fn updates_from_other_threads_are_applied_on_tick() {
+    let (go, wait) = mpsc::channel();
+    let harness = TestHarness::new_component::<Worker>(wait);
+
+    harness.click("start");
+    harness.tick();
+    assert_eq!(harness.get_by_id("result").text(), "working");
+
+    go.send(()).unwrap();
+    // wait for the worker to send its result
+    let result = loop {
+        harness.tick();
+        let result = harness.get_by_id("result").text();
+        if result != "working" {
+            break result;
+        }
+        thread::yield_now();
+    };
+    assert_eq!(result, "55");
+}
+
+#[test]
+fn existing_send_links_use_the_new_wake_hook() {
+    let manager = StateManager::new(0);
+    let link = manager.link().send_link();
+
+    let wakes = Arc::new(AtomicUsize::new(0));
+    set_wake({
+        let wakes = wakes.clone();
+        move || {
+            wakes.fetch_add(1, Ordering::SeqCst);
+        }
+    });
+    thread::spawn({
+        let link = link.clone();
+        move || link.send_update(|value| *value += 1)
+    }).join().unwrap();
+    assert_eq!(wakes.load(Ordering::SeqCst), 1);
+    drain_sent_updates();
+    assert_eq!(manager.on_state(|value| *value), 1);
+
+    // the updates sent after the state has been dropped and drained go nowhere
+    drop(manager);
+    drain_sent_updates();
+    link.send_update(|value| *value += 1);
+    assert_eq!(wakes.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,158 @@
+use std::cell::Cell;
+
+use regui::{signal::{untrack, Effect, Memo, Signal}, utils::PtrEqRc, LiveValue};
+use regui_test::prelude::*;
+use controls::{Button, Label, VerticalBox, Window};
+
+
+#[derive(Clone, PartialEq)]
+struct Renders {
+    app: PtrEqRc<Cell<usize>>,
+    count: PtrEqRc<Cell<usize>>,
+    parity: PtrEqRc<Cell<usize>>,
+}
+
+impl Renders {
+    fn new() -> Self {
+        Self {
+            app: PtrEqRc::new(Cell::new(0)),
+            count: PtrEqRc::new(Cell::new(0)),
+            parity: PtrEqRc::new(Cell::new(0)),
+        }
+    }
+}
+
+decl_function_component!(Count count((Signal<i32>, Renders)) -> Node);
+
+fn count((count, renders): &(Signal<i32>, Renders), cx: &mut Cx) -> Node {
+    renders.count.set(renders.count.get() + 1);
+    Label::builder()
+        .id("count")
+        .text(&count.get().to_string())
+        .get(cx)
+}
+
+decl_function_component!(Parity parity((Memo<bool>, Renders)) -> Node);
+
+fn parity((even, renders): &(Memo<bool>, Renders), cx: &mut Cx) -> Node {
+    renders.parity.set(renders.parity.get() + 1);
+    Label::builder()
+        .id("parity")
+        .text(if even.get() { "even" } else { "odd" })
+        .get(cx)
+}
+
+decl_function_component!(App app(Renders) -> ());
+
+fn app(renders: &Renders, cx: &mut Cx) {
+    renders.app.set(renders.app.get() + 1);
+    let count = cx.use_signal(|| 0);
+    let even = cx.use_computed({
+        let count = count.clone();
+        move || count.get() % 2 == 0
+    });
+
+    let add_one = Button::builder()
+        .id("add_one")
+        .on_click({
+            let count = count.clone();
+            move || count.update(|count| *count += 1)
+        })
+        .get(cx);
+    let add_two = Button::builder()
+        .id("add_two")
+        .on_click({
+            let count = count.clone();
+            move || count.update(|count| *count += 2)
+        })
+        .get(cx);
+    let vbox = VerticalBox::builder()
+        .child(add_one)
+        .child(add_two)
+        .child(Count::eval(cx, (count, renders.clone())))
+        .child(Parity::eval(cx, (even, renders.clone())))
+        .get(cx);
+
+    Window::builder()
+        .child(vbox)
+        .get(cx);
+}
+
+#[test]
+fn signals_render_only_their_readers() {
+    let renders = Renders::new();
+    let harness = TestHarness::new::<App>(renders.clone());
+    assert_eq!(harness.get_by_id("count").text(), "0");
+    assert_eq!(harness.get_by_id("parity").text(), "even");
+    assert_eq!((renders.app.get(), renders.count.get(), renders.parity.get()), (1, 1, 1));
+
+    harness.click("add_one");
+    assert_eq!(harness.get_by_id("count").text(), "1");
+    assert_eq!(harness.get_by_id("parity").text(), "odd");
+    assert_eq!((renders.app.get(), renders.count.get(), renders.parity.get()), (1, 2, 2));
+
+    // the memo is recomputed but has not changed
+    harness.click("add_two");
+    assert_eq!(harness.get_by_id("count").text(), "3");
+    assert_eq!(harness.get_by_id("parity").text(), "odd");
+    assert_eq!((renders.app.get(), renders.count.get(), renders.parity.get()), (1, 3, 2));
+}
+
+#[test]
+fn memos_are_computed_lazily() {
+    let computed = PtrEqRc::new(Cell::new(0));
+    let count = Signal::new(1);
+    let double = Memo::new({
+        let (count, computed) = (count.clone(), computed.clone());
+        move || {
+            computed.set(computed.get() + 1);
+            count.get() * 2
+        }
+    });
+    assert_eq!(computed.get(), 0);
+
+    count.set(2);
+    count.set(3);
+    assert_eq!(computed.get(), 0);
+    assert_eq!(double.get(), 6);
+    assert_eq!(double.get(), 6);
+    assert_eq!(computed.get(), 1);
+}
+
+#[test]
+fn signals_convert_to_and_from_live_values() {
+    let source = Signal::new(1);
+    let live: LiveValue<i32> = (&source).into();
+    assert_eq!(live.value, 1);
+
+    let follower = Signal::from_emitter(&live.emitter, {
+        let source = source.clone();
+        move || source.get_untracked() * 10
+    });
+    assert_eq!(follower.get(), 10);
+
+    source.set(2);
+    assert_eq!(follower.get(), 20);
+}
+
+#[test]
+fn effects_still_run_after_a_panicking_effect() {
+    let (count, other) = (Signal::new(0), Signal::new(0));
+    let seen = PtrEqRc::new(Cell::new(0));
+    let _panicking = Effect::new({
+        let count = count.clone();
+        move || assert!(count.get() != 1, "the effect panics")
+    });
+    let _effect = Effect::new({
+        let (other, seen) = (other.clone(), seen.clone());
+        move || seen.set(other.get())
+    });
+
+    let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| count.set(1)));
+    assert!(panic.is_err());
+    let panic = std::panic::catch_unwind(|| untrack(|| panic!("untracked")));
+    assert!(panic.is_err());
+
+    other.set(2);
+    assert_eq!(seen.get(), 2);
+}
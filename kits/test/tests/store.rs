@@ -0,0 +1,226 @@
+use std::{cell::{Cell, RefCell}, rc::Rc};
+
+use regui::{store::{Next, Reducible, Store}, utils::PtrEqRc};
+use regui_test::prelude::*;
+use controls::{Button, Label, VerticalBox, Window};
+
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct AppState {
+    count: i32,
+    name: String,
+}
+
+enum Action {
+    Increment,
+    Rename(String),
+    Undo,
+}
+
+impl Reducible for AppState {
+    type Action = Action;
+    fn reduce(&mut self, action: Action) {
+        match action {
+            Action::Increment => self.count += 1,
+            Action::Rename(name) => self.name = name,
+            Action::Undo => {}
+        }
+    }
+}
+
+/// Records the states before the actions, and restores them on [`Action::Undo`].
+fn undo_middleware() -> impl FnMut(Action, Next<AppState>) {
+    let mut history = Vec::new();
+    move |action, mut next| match action {
+        Action::Undo => {
+            if let Some(previous) = history.pop() {
+                *next.state_mut() = previous;
+            }
+        }
+        action => {
+            history.push(next.state().clone());
+            next.run(action);
+        }
+    }
+}
+
+decl_function_component!(Count count(PtrEqRc<Cell<usize>>) -> Node);
+
+fn count(renders: &PtrEqRc<Cell<usize>>, cx: &mut Cx) -> Node {
+    renders.set(renders.get() + 1);
+    let count = cx.use_store(|state: &AppState| state.count);
+    Label::builder()
+        .id("count")
+        .text(&count.to_string())
+        .get(cx)
+}
+
+decl_function_component!(Name name(PtrEqRc<Cell<usize>>) -> Node);
+
+fn name(renders: &PtrEqRc<Cell<usize>>, cx: &mut Cx) -> Node {
+    renders.set(renders.get() + 1);
+    let name = cx.use_store(|state: &AppState| state.name.clone());
+    let store = cx.use_context::<Store<AppState>>().unwrap();
+    let rename = Button::builder()
+        .id("rename")
+        .on_click(move || store.dispatch(Action::Rename("renamed".into())))
+        .get(cx);
+    let label = Label::builder()
+        .id("name")
+        .text(&name)
+        .get(cx);
+    VerticalBox::builder()
+        .child(rename)
+        .child(label)
+        .get(cx)
+}
+
+#[derive(Clone, PartialEq)]
+struct Renders {
+    count: PtrEqRc<Cell<usize>>,
+    name: PtrEqRc<Cell<usize>>,
+}
+
+decl_function_component!(App app(Renders) -> ());
+
+fn app(renders: &Renders, cx: &mut Cx) {
+    let store = cx.use_ref(|| Store::new(AppState::default()).with_middleware(undo_middleware()));
+    cx.provide_context((*store).clone());
+
+    let increment = Button::builder()
+        .id("increment")
+        .on_click({
+            let store = store.clone();
+            move || store.dispatch(Action::Increment)
+        })
+        .get(cx);
+    let undo = Button::builder()
+        .id("undo")
+        .on_click({
+            let store = store.clone();
+            move || store.dispatch(Action::Undo)
+        })
+        .get(cx);
+    let vbox = VerticalBox::builder()
+        .child(increment)
+        .child(undo)
+        .child(Count::eval(cx, renders.count.clone()))
+        .child(Name::eval(cx, renders.name.clone()))
+        .get(cx);
+
+    Window::builder()
+        .child(vbox)
+        .get(cx);
+}
+
+#[test]
+fn components_render_when_their_selection_changes() {
+    let renders = Renders {
+        count: PtrEqRc::new(Cell::new(0)),
+        name: PtrEqRc::new(Cell::new(0)),
+    };
+    let harness = TestHarness::new::<App>(renders.clone());
+    assert_eq!((renders.count.get(), renders.name.get()), (1, 1));
+
+    harness.click("increment");
+    harness.click("increment");
+    assert_eq!(harness.get_by_id("count").text(), "2");
+    assert_eq!((renders.count.get(), renders.name.get()), (3, 1));
+
+    harness.click("rename");
+    assert_eq!(harness.get_by_id("name").text(), "renamed");
+    assert_eq!((renders.count.get(), renders.name.get()), (3, 2));
+
+    harness.click("undo");
+    harness.click("undo");
+    assert_eq!(harness.get_by_id("name").text(), "");
+    assert_eq!(harness.get_by_id("count").text(), "1");
+}
+
+#[test]
+fn actions_dispatched_by_middlewares_are_queued() {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let store = Rc::new(RefCell::new(None::<Store<AppState>>));
+    let created = Store::new(AppState::default())
+        .with_middleware({
+            let (log, store) = (log.clone(), store.clone());
+            move |action: Action, next: Next<AppState>| {
+                let increment = matches!(action, Action::Increment);
+                log.borrow_mut().push(format!("start {}", next.state().count));
+                next.run(action);
+                if increment {
+                    // runs after this action
+                    store.borrow().as_ref().unwrap().dispatch(Action::Rename("incremented".into()));
+                }
+                log.borrow_mut().push("end".to_string());
+            }
+        });
+    *store.borrow_mut() = Some(created.clone());
+
+    let notified = Rc::new(Cell::new(0));
+    let _subscription = created.listen({
+        let notified = notified.clone();
+        move |_| notified.set(notified.get() + 1)
+    });
+
+    created.dispatch(Action::Increment);
+    assert_eq!(*log.borrow(), vec!["start 0", "end", "start 1", "end"]);
+    assert_eq!(created.get(), AppState { count: 1, name: "incremented".into() });
+    // once for both actions
+    assert_eq!(notified.get(), 1);
+    store.borrow_mut().take();
+}
+
+#[test]
+fn stores_accept_actions_after_a_panicking_action() {
+    let store = Store::new(AppState::default())
+        .with_middleware(|action: Action, next: Next<AppState>| {
+            assert!(!matches!(&action, Action::Rename(name) if name == "panic"), "the middleware panics");
+            next.run(action);
+        });
+
+    let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| store.dispatch(Action::Rename("panic".into()))));
+    assert!(panic.is_err());
+
+    store.dispatch(Action::Increment);
+    assert_eq!(store.get(), AppState { count: 1, name: String::new() });
+}
+
+decl_function_component!(Even even(()) -> Node);
+
+/// Rounds the count up to an even number.
+fn even(_props: &(), cx: &mut Cx) -> Node {
+    let count = cx.use_store(|state: &AppState| state.count);
+    let store = cx.use_context::<Store<AppState>>().unwrap();
+    cx.use_effect(count, move |count| {
+        if count % 2 == 1 {
+            store.dispatch(Action::Increment);
+        }
+        || {}
+    });
+    Label::builder()
+        .id("even")
+        .text(&count.to_string())
+        .get(cx)
+}
+
+decl_function_component!(EvenApp even_app(Store<AppState>) -> ());
+
+fn even_app(store: &Store<AppState>, cx: &mut Cx) {
+    cx.provide_context(store.clone());
+    let even = Even::eval(cx, ());
+    Window::builder()
+        .child(even)
+        .get(cx);
+}
+
+#[test]
+fn effects_can_dispatch_when_rendered_by_a_dispatch_outside_of_a_batch() {
+    let store = Store::new(AppState::default());
+    let harness = TestHarness::new::<EvenApp>(store.clone());
+
+    // not from an event callback, the consumer is rendered while the store notifies
+    store.dispatch(Action::Increment);
+    assert_eq!(store.get().count, 2);
+    assert_eq!(harness.get_by_id("even").text(), "2");
+}
@@ -0,0 +1,268 @@
+use std::{rc::Rc, cell::RefCell, collections::VecDeque, pin::Pin, task::{Context, Poll, Waker}, time::Duration};
+
+use futures_core::Stream;
+use regui::{LiveLink, component::{Command, Component, FunctionsCache, LiveStateComponent, ShouldRender, StateLink}, subscription::Subscription};
+use regui_test::prelude::*;
+use controls::{Button, Label, VerticalBox, Window};
+
+fn eval<F: ComponentFunction>(cache: &FunctionsCache, props: F::Props) -> F::Out {
+    cache.eval_live::<LiveStateComponent<FunctionComponent<F>>, F::Out>(props)
+}
+
+enum Message {
+    Tick,
+    Toggle,
+}
+
+/// Ticks every second while running.
+#[derive(Default)]
+struct Clock {
+    running: bool,
+    seconds: usize,
+}
+
+impl Component for Clock {
+    type Props = ();
+    type Out = ();
+    type Message = Message;
+
+    fn build(_props: (), _link: StateLink<Self>) -> Self {
+        Self::default()
+    }
+
+    fn on_message(&mut self, message: Message, _link: StateLink<Self>) -> Command<Message> {
+        match message {
+            Message::Tick => self.seconds += 1,
+            Message::Toggle => self.running = !self.running,
+        }
+        Command::none()
+    }
+
+    fn subscriptions(&self) -> Vec<Subscription<Message>> {
+        if self.running {
+            vec![Subscription::interval("clock", Duration::from_secs(1), || Message::Tick)]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn view(&self, link: StateLink<Self>, cache: &FunctionsCache) {
+        let toggle = eval::<Button>(cache, Button::builder()
+            .id("toggle")
+            .on_click(move || link.send_message(Message::Toggle)));
+        let seconds = eval::<Label>(cache, Label::builder()
+            .id("seconds")
+            .text(&self.seconds.to_string()));
+        let vbox = eval::<VerticalBox>(cache, VerticalBox::builder()
+            .child(toggle)
+            .child(seconds));
+        eval::<Window>(cache, Window::builder().child(vbox));
+    }
+}
+
+#[test]
+fn subscriptions_follow_the_state() {
+    let harness = TestHarness::new_component::<Clock>(());
+
+    harness.advance(Duration::from_secs(2));
+    assert_eq!(harness.get_by_id("seconds").text(), "0");
+
+    harness.click("toggle");
+    harness.tick();
+    harness.advance(Duration::from_secs(2));
+    assert_eq!(harness.get_by_id("seconds").text(), "2");
+
+    // the renders caused by the ticks keep the same interval
+    harness.advance(Duration::from_millis(500));
+    assert_eq!(harness.get_by_id("seconds").text(), "2");
+
+    harness.click("toggle");
+    harness.advance(Duration::from_secs(2));
+    assert_eq!(harness.get_by_id("seconds").text(), "2");
+}
+
+/// A stream of the values pushed by the test.
+#[derive(Clone, Default)]
+struct Channel(Rc<RefCell<(VecDeque<String>, Option<Waker>)>>);
+
+impl Channel {
+    fn push(&self, value: &str) {
+        let mut inner = self.0.borrow_mut();
+        inner.0.push_back(value.into());
+        if let Some(waker) = inner.1.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl Stream for Channel {
+    type Item = String;
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<String>> {
+        let mut inner = self.0.borrow_mut();
+        match inner.0.pop_front() {
+            Some(value) => Poll::Ready(Some(value)),
+            None => {
+                inner.1 = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Sources {
+    channel: Channel,
+    link: LiveLink,
+}
+
+decl_function_component!(Feed feed(Sources) -> Node);
+
+fn feed(sources: &Sources, cx: &mut Cx) -> Node {
+    let last = cx.use_state(|| "".to_string());
+    let changes = cx.use_state(|| 0);
+
+    cx.use_subscription(Some(Subscription::stream("feed", sources.channel.clone(), |value| value)), {
+        let last = last.clone();
+        move |value| last.set(value)
+    });
+    let emitter = sources.link.make_live_value(()).emitter;
+    cx.use_subscription(Some(Subscription::live_value("changes", emitter, || ())), {
+        let changes = changes.clone();
+        move |()| changes.set(changes.get() + 1)
+    });
+
+    Label::builder()
+        .id("feed")
+        .text(&format!("{} ({})", last.get(), changes.get()))
+        .get(cx)
+}
+
+decl_function_component!(App app(Sources) -> ());
+
+fn app(sources: &Sources, cx: &mut Cx) {
+    let visible = cx.use_state(|| true);
+
+    let hide = Button::builder()
+        .id("hide")
+        .on_click({
+            let visible = visible.clone();
+            move || visible.set(false)
+        })
+        .get(cx);
+    let mut vbox = VerticalBox::builder().child(hide);
+    if visible.get() {
+        vbox = vbox.child(Feed::eval(cx, sources.clone()));
+    }
+    let vbox = vbox.get(cx);
+
+    Window::builder()
+        .child(vbox)
+        .get(cx);
+}
+
+#[test]
+fn streams_and_live_values_are_cancelled_when_removed() {
+    let sources = Sources { channel: Channel::default(), link: LiveLink::new() };
+    let harness = TestHarness::new::<App>(sources.clone());
+
+    sources.channel.push("a");
+    sources.channel.push("b");
+    harness.tick();
+    assert_eq!(harness.get_by_id("feed").text(), "b (0)");
+
+    sources.link.tell_update();
+    harness.tick();
+    assert_eq!(harness.get_by_id("feed").text(), "b (1)");
+
+    harness.click("hide");
+    assert!(harness.find_by_id("feed").is_none());
+
+    // nothing listens anymore
+    sources.channel.push("c");
+    sources.link.tell_update();
+    harness.tick();
+    assert_eq!(sources.channel.0.borrow().0.len(), 1);
+}
+
+/// Counts the ticks of an interval whose period is given by the parent.
+struct Ticker {
+    period: Duration,
+    ticks: usize,
+}
+
+impl Component for Ticker {
+    type Props = Duration;
+    type Out = Node;
+    type Message = ();
+
+    fn build(period: Duration, _link: StateLink<Self>) -> Self {
+        Self { period, ticks: 0 }
+    }
+
+    /// The view does not depend on the period.
+    fn update(&mut self, period: Duration, _link: StateLink<Self>) -> ShouldRender {
+        self.period = period;
+        false
+    }
+
+    fn on_message(&mut self, _message: (), _link: StateLink<Self>) -> Command<()> {
+        self.ticks += 1;
+        Command::none()
+    }
+
+    fn subscriptions(&self) -> Vec<Subscription<()>> {
+        vec![Subscription::interval("ticker", self.period, || ())]
+    }
+
+    fn view(&self, _link: StateLink<Self>, cache: &FunctionsCache) -> Node {
+        eval::<Label>(cache, Label::builder()
+            .id("ticks")
+            .text(&self.ticks.to_string()))
+    }
+}
+
+/// Slows its ticker down on click.
+struct Metronome {
+    period: Duration,
+}
+
+impl Component for Metronome {
+    type Props = ();
+    type Out = ();
+    type Message = ();
+
+    fn build(_props: (), _link: StateLink<Self>) -> Self {
+        Self { period: Duration::from_secs(1) }
+    }
+
+    fn on_message(&mut self, _message: (), _link: StateLink<Self>) -> Command<()> {
+        self.period *= 10;
+        Command::none()
+    }
+
+    fn view(&self, link: StateLink<Self>, cache: &FunctionsCache) {
+        let slower = eval::<Button>(cache, Button::builder()
+            .id("slower")
+            .on_click(move || link.send_message(())));
+        let ticker = cache.eval_live::<LiveStateComponent<Ticker>, Node>(self.period);
+        let vbox = eval::<VerticalBox>(cache, VerticalBox::builder()
+            .child(slower)
+            .child(ticker));
+        eval::<Window>(cache, Window::builder().child(vbox));
+    }
+}
+
+#[test]
+fn subscriptions_restart_when_their_params_change_without_a_view() {
+    let harness = TestHarness::new_component::<Metronome>(());
+    harness.tick();
+    harness.advance(Duration::from_secs(2));
+    assert_eq!(harness.get_by_id("ticks").text(), "2");
+
+    harness.click("slower");
+    harness.tick();
+    harness.advance(Duration::from_secs(5));
+    assert_eq!(harness.get_by_id("ticks").text(), "2");
+    harness.advance(Duration::from_secs(5));
+    assert_eq!(harness.get_by_id("ticks").text(), "3");
+}
@@ -0,0 +1,79 @@
+use std::{rc::Rc, cell::RefCell};
+
+use regui::component::{StateManager, UpdateLimits, UpdateLoopError, UpdateLoopKind, set_update_limits, set_update_loop_handler};
+use regui_test::prelude::*;
+use controls::{Label, Window};
+
+decl_function_component!(Runaway runaway(()) -> ());
+
+/// Every view changes the dependencies of the effect, which sets the state again.
+fn runaway(_props: &(), cx: &mut Cx) {
+    let count = cx.use_state(|| 0);
+
+    cx.use_effect(count.get(), {
+        let count = count.clone();
+        move |value| {
+            count.set(value + 1);
+            || {}
+        }
+    });
+
+    let label = Label::builder()
+        .id("count")
+        .text(&count.get().to_string())
+        .get(cx);
+
+    Window::builder()
+        .child(label)
+        .get(cx);
+}
+
+fn collect_errors() -> Rc<RefCell<Vec<UpdateLoopError>>> {
+    let errors = Rc::new(RefCell::new(Vec::new()));
+    set_update_loop_handler({
+        let errors = errors.clone();
+        move |error| errors.borrow_mut().push(error.clone())
+    });
+    errors
+}
+
+#[test]
+fn update_loops_are_reported() {
+    let errors = collect_errors();
+    set_update_limits(UpdateLimits { max_reruns: 10, ..Default::default() });
+
+    let harness = TestHarness::new::<Runaway>(());
+
+    let errors = errors.borrow();
+    assert!(!errors.is_empty());
+    let error = &errors[0];
+    assert_eq!(error.kind, UpdateLoopKind::TooManyReruns);
+    assert_eq!(error.iterations, 11);
+    assert!(error.component.contains("Runaway"), "{}", error.component);
+    assert!(error.origins.iter().all(|origin| origin.file().ends_with("update_loop.rs")), "{}", error);
+    assert!(error.to_string().contains("infinite update loop"));
+
+    // the application is still alive
+    assert!(harness.get_by_id("count").text().parse::<usize>().is_ok());
+}
+
+#[test]
+fn only_the_messages_exceeding_the_queue_are_discarded() {
+    let errors = collect_errors();
+    set_update_limits(UpdateLimits { max_queue_len: 3, ..Default::default() });
+
+    let manager = StateManager::new(0);
+    manager.set_builder(|value: &i32, link| {
+        if *value == 0 {
+            // queued while the state is rendered
+            for _ in 0..5 {
+                link.send_update(|value| *value += 1);
+            }
+        }
+    });
+
+    assert_eq!(manager.on_state(|value| *value), 3);
+    let errors = errors.borrow();
+    assert_eq!(errors.len(), 2);
+    assert!(errors.iter().all(|error| error.kind == UpdateLoopKind::QueueTooLong && error.iterations == 4));
+}
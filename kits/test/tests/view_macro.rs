@@ -0,0 +1,45 @@
+use regui::view;
+use regui_test::prelude::*;
+use controls::{Button, Entry, Label, Window};
+
+#[component]
+fn todo_list(items: &Vec<&'static str>, cx: &mut Cx) {
+    let done = cx.use_state(|| false);
+    let footer = Label::builder()
+        .id("footer")
+        .text(&format!("{} items", items.len()))
+        .get(cx);
+
+    view! { cx,
+        <Window>
+            <controls::VerticalBox>
+                <Label id="title" text="Todo" />
+                if done.get() {
+                    <Label id="done" text="Done!" />
+                } else {
+                    <Button id="finish" on_click={move || done.set(true)} />
+                    <Entry id="entry" />
+                }
+                for (i, item) in items.iter().enumerate() {
+                    <Label id={&format!("item-{}", i)} text={item} />
+                }
+                {footer}
+            </controls::VerticalBox>
+        </Window>
+    };
+}
+
+#[test]
+fn view_expands_to_builders() {
+    let harness = TestHarness::new::<TodoList>(vec!["milk", "eggs"]);
+    assert_eq!(harness.get_by_id("title").text(), "Todo");
+    assert_eq!(harness.get_by_id("item-0").text(), "milk");
+    assert_eq!(harness.get_by_id("item-1").text(), "eggs");
+    assert_eq!(harness.get_by_id("footer").text(), "2 items");
+    assert!(harness.find_by_id("entry").is_some());
+
+    harness.click("finish");
+    assert_eq!(harness.get_by_id("done").text(), "Done!");
+    assert!(harness.find_by_id("finish").is_none());
+    assert!(harness.find_by_id("entry").is_none());
+}
@@ -0,0 +1,243 @@
+//! Procedural macros for `regui`, re-exported by the `regui` crate.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, quote_spanned};
+use proc_macro_crate::{crate_name, FoundCrate};
+use syn::{
+    parse::{Parse, ParseStream}, parse_macro_input, spanned::Spanned,
+    FnArg, Ident, ItemFn, Path, ReturnType, Token, Type,
+};
+
+mod view;
+
+/// Declares a function component from its function.
+///
+/// ```ignore
+/// #[component]
+/// pub fn my_button(props: &MyButtonProps, cx: &mut Cx) -> Node {
+///     // ...
+/// }
+/// ```
+/// declares the marker struct `MyButton`, with the visibility of the function, implementing
+/// `ComponentFunction`. The struct also has:
+/// - `MyButton::eval(cx, props)`, that does not need `ComponentFunction` in scope,
+/// - `MyButton::default_props()`, returning the default props if they implement `Default`,
+///   to be completed with the builder methods of the props.
+///
+/// The options are:
+/// - `#[component(memo)]` skips the views when the parent passes equal props, see `ComponentFunction::props_equal`,
+/// - `#[component(Name)]` names the struct `Name` instead of the function name in `UpperCamelCase`,
+/// - `#[component(crate = path)]` gives the path of the `regui` crate. By default it is found in
+///   the `Cargo.toml` of the crate using the macro, even if renamed there.
+///
+/// They can be combined: `#[component(memo, Name)]`.
+#[proc_macro_attribute]
+pub fn component(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as Args);
+    let function = parse_macro_input!(item as ItemFn);
+    expand(args, function).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+/// Builds a tree of controls with a markup close to JSX.
+///
+/// ```ignore
+/// view! { cx,
+///     <Window>
+///         <VerticalBox>
+///             <Label id="greeting" text={&format!("Hello {}", name)} />
+///             if logged_in {
+///                 <Button text="Log out" on_click={move || log_out()} />
+///             } else {
+///                 <Entry on_changed={move |text| set_name(text)} />
+///             }
+///             for item in items.iter() {
+///                 <Label text={item} />
+///             }
+///             {footer}
+///         </VerticalBox>
+///     </Window>
+/// }
+/// ```
+///
+/// Each element `<Name attribute={value} ...>` expands to the builder calls
+/// `Name::builder().attribute(value)...`, then the children are added with `.child(node)`,
+/// and the element is evaluated with `.get(cx)`. So the macro works with any kit whose controls follow this pattern.
+///
+/// - An attribute value is either a literal or an expression in braces. An attribute without
+///   value is `true`, e.g. `<Checkbox checked />`.
+/// - `{expr}` adds a node built elsewhere, e.g. with `eval_keyed`.
+/// - `if`, `else` and `for` blocks add their children conditionally or repeatedly.
+/// - `child_args={args}` passes more arguments to the `child` method of the parent, after the node:
+///   a tuple passes several arguments.
+/// - The arguments between `cx,` and `=>` are passed to every `builder` call:
+///
+/// ```ignore
+/// view! { cx, ui =>
+///     <Window title="Counter">
+///         <VerticalBox>
+///             <Label text="Hello" child_args={LayoutStrategy::Compact} />
+///         </VerticalBox>
+///     </Window>
+/// }
+/// ```
+///
+/// The macro evaluates to the output of the root element.
+#[proc_macro]
+pub fn view(input: TokenStream) -> TokenStream {
+    parse_macro_input!(input as view::View).expand().into()
+}
+
+#[derive(Default)]
+struct Args {
+    memo: bool,
+    name: Option<Ident>,
+    krate: Option<Path>,
+}
+
+impl Parse for Args {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = Args::default();
+        while !input.is_empty() {
+            if input.peek(Token![crate]) {
+                let token: Token![crate] = input.parse()?;
+                if args.krate.is_some() {
+                    return Err(syn::Error::new(token.span, "`crate` is given twice"));
+                }
+                input.parse::<Token![=]>()?;
+                args.krate = Some(input.parse()?);
+                if !input.is_empty() {
+                    input.parse::<Token![,]>()?;
+                }
+                continue;
+            }
+            let ident: Ident = input.parse()?;
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+            if ident == "memo" {
+                if args.memo {
+                    return Err(syn::Error::new(ident.span(), "`memo` is given twice"));
+                }
+                args.memo = true;
+            } else if let Some(name) = &args.name {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    format!("the component is already named `{}`, expected `memo` or a single name", name),
+                ));
+            } else {
+                args.name = Some(ident);
+            }
+        }
+        Ok(args)
+    }
+}
+
+fn expand(args: Args, function: ItemFn) -> syn::Result<TokenStream2> {
+    let signature = &function.sig;
+    if let Some(asyncness) = &signature.asyncness {
+        return Err(syn::Error::new(asyncness.span(), "components cannot be async, use `cx.use_future` instead"));
+    }
+    if !signature.generics.params.is_empty() || signature.generics.where_clause.is_some() {
+        return Err(syn::Error::new(signature.generics.span(), "components cannot be generic"));
+    }
+    if signature.inputs.len() != 2 {
+        return Err(syn::Error::new(
+            signature.inputs.span(),
+            "components take two arguments: `(props: &Props, cx: &mut Cx)`",
+        ));
+    }
+
+    let props = props_type(&signature.inputs[0])?;
+    let out = match &signature.output {
+        ReturnType::Default => quote! { () },
+        ReturnType::Type(_, ty) => quote! { #ty },
+    };
+
+    let vis = &function.vis;
+    let func_name = &signature.ident;
+    let name = args.name.unwrap_or_else(|| Ident::new(&upper_camel_case(&func_name.to_string()), func_name.span()));
+    let docs = function.attrs.iter().filter(|attr| attr.path().is_ident("doc"));
+    let krate = match args.krate {
+        Some(path) => quote! { #path::function_component },
+        None => regui_path(),
+    };
+
+    // the errors about the arguments and the output point to the signature
+    let call = quote_spanned! {signature.span()=>
+        #func_name(props, cx)
+    };
+    let memo = args.memo.then(|| quote_spanned! {props.span()=>
+        fn props_equal(old: &Self::Props, new: &Self::Props) -> bool {
+            old == new
+        }
+    });
+
+    Ok(quote! {
+        #function
+
+        #(#docs)*
+        #vis struct #name;
+
+        impl #krate::ComponentFunction for #name {
+            type Props = #props;
+            type Out = #out;
+            fn call(props: &Self::Props, cx: &mut #krate::Cx) -> Self::Out {
+                #call
+            }
+            #memo
+        }
+
+        #[allow(dead_code)]
+        impl #name {
+            /// Evaluates the component as a child of the component owning `cx`.
+            #vis fn eval(cx: &mut #krate::Cx, props: #props) -> #out {
+                <Self as #krate::ComponentFunction>::eval(cx, props)
+            }
+
+            /// The default props, to be completed then passed to [`eval`](Self::eval).
+            #vis fn default_props<P: ::core::default::Default>() -> P
+            where
+                Self: #krate::ComponentFunction<Props = P>,
+            {
+                P::default()
+            }
+        }
+    })
+}
+
+/// The path of the `function_component` module of `regui`, as named by the crate using the macro.
+///
+/// `regui` itself and its doctests refer to it as `::regui`. If it cannot be found, e.g. when it
+/// is only a dependency of a kit, `::regui` is assumed and the path can be given with `crate = path`.
+fn regui_path() -> TokenStream2 {
+    match crate_name("regui") {
+        Ok(FoundCrate::Name(name)) => {
+            let name = Ident::new(&name, Span::call_site());
+            quote! { ::#name::function_component }
+        }
+        Ok(FoundCrate::Itself) | Err(_) => quote! { ::regui::function_component },
+    }
+}
+
+/// The props type of the first argument, which must be a shared reference.
+fn props_type(arg: &FnArg) -> syn::Result<&Type> {
+    let FnArg::Typed(arg) = arg else {
+        return Err(syn::Error::new(arg.span(), "components are functions, not methods"));
+    };
+    match &*arg.ty {
+        Type::Reference(reference) if reference.mutability.is_none() => Ok(&reference.elem),
+        ty => Err(syn::Error::new(ty.span(), "the props must be taken by shared reference, e.g. `props: &Props`")),
+    }
+}
+
+fn upper_camel_case(name: &str) -> String {
+    name.trim_start_matches("r#")
+        .split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().into_iter().flat_map(char::to_uppercase).chain(chars).collect::<String>()
+        })
+        .collect()
+}
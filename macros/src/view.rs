@@ -0,0 +1,269 @@
+//! The `view!` macro: a tree of elements expanded into builder calls.
+
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned, ToTokens};
+use syn::{
+    braced, ext::IdentExt, parse::{Parse, ParseStream}, spanned::Spanned, token, Expr, Ident, Lit, Pat, Path, Token,
+};
+
+/// `cx, args... => root`
+pub(crate) struct View {
+    cx: Expr,
+    builder_args: Vec<Expr>,
+    root: Child,
+}
+
+/// An element, with its builder calls and its children.
+struct Element {
+    name: Path,
+    attributes: Vec<Attribute>,
+    /// Passed to the `child` method of the parent after the element.
+    child_args: Option<Expr>,
+    children: Vec<Child>,
+}
+
+struct Attribute {
+    name: Ident,
+    value: TokenStream,
+}
+
+enum Child {
+    Element(Element),
+    /// `{expr}`, a value already built.
+    Expr(Expr),
+    If {
+        condition: Expr,
+        then: Vec<Child>,
+        otherwise: Option<Vec<Child>>,
+    },
+    For {
+        pat: Pat,
+        iter: Expr,
+        body: Vec<Child>,
+    },
+}
+
+impl Parse for View {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let cx = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let mut builder_args = Vec::new();
+        if !input.peek(Token![<]) {
+            loop {
+                builder_args.push(input.parse()?);
+                if input.peek(Token![=>]) {
+                    break;
+                }
+                input.parse::<Token![,]>()?;
+            }
+            input.parse::<Token![=>]>()?;
+        }
+        let root = input.parse()?;
+        if !input.is_empty() {
+            return Err(input.error("a view has a single root, wrap the elements in a container"));
+        }
+        Ok(Self { cx, builder_args, root })
+    }
+}
+
+impl Parse for Child {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Token![<]) {
+            input.parse().map(Child::Element)
+        } else if input.peek(token::Brace) {
+            let content;
+            braced!(content in input);
+            content.parse().map(Child::Expr)
+        } else if input.peek(Token![if]) {
+            input.parse::<Token![if]>()?;
+            let condition = Expr::parse_without_eager_brace(input)?;
+            let then = parse_children_block(input)?;
+            let otherwise = if input.peek(Token![else]) {
+                input.parse::<Token![else]>()?;
+                if input.peek(Token![if]) {
+                    Some(vec![input.parse()?])
+                } else {
+                    Some(parse_children_block(input)?)
+                }
+            } else {
+                None
+            };
+            Ok(Child::If { condition, then, otherwise })
+        } else if input.peek(Token![for]) {
+            input.parse::<Token![for]>()?;
+            let pat = Pat::parse_single(input)?;
+            input.parse::<Token![in]>()?;
+            let iter = Expr::parse_without_eager_brace(input)?;
+            let body = parse_children_block(input)?;
+            Ok(Child::For { pat, iter, body })
+        } else {
+            Err(input.error("expected an element `<Name ...>`, a value `{...}`, `if` or `for`"))
+        }
+    }
+}
+
+/// `{ children }`
+fn parse_children_block(input: ParseStream) -> syn::Result<Vec<Child>> {
+    let content;
+    braced!(content in input);
+    let mut children = Vec::new();
+    while !content.is_empty() {
+        children.push(content.parse()?);
+    }
+    Ok(children)
+}
+
+impl Parse for Element {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        input.parse::<Token![<]>()?;
+        let name = Path::parse_mod_style(input)?;
+        let mut attributes = Vec::new();
+        let mut child_args = None;
+        while !input.peek(Token![>]) && !input.peek(Token![/]) {
+            let attribute_name = Ident::parse_any(input)?;
+            let value = if input.peek(Token![=]) {
+                input.parse::<Token![=]>()?;
+                if input.peek(token::Brace) {
+                    let content;
+                    braced!(content in input);
+                    content.parse::<Expr>()?.into_token_stream()
+                } else {
+                    input.parse::<Lit>()?.into_token_stream()
+                }
+            } else {
+                // `<Checkbox checked />`
+                quote_spanned! {attribute_name.span()=> true }
+            };
+            if attribute_name == "child_args" {
+                child_args = Some(syn::parse2(value)?);
+            } else {
+                attributes.push(Attribute { name: attribute_name, value });
+            }
+        }
+
+        let mut children = Vec::new();
+        if input.peek(Token![/]) {
+            input.parse::<Token![/]>()?;
+            input.parse::<Token![>]>()?;
+        } else {
+            input.parse::<Token![>]>()?;
+            while !(input.peek(Token![<]) && input.peek2(Token![/])) {
+                if input.is_empty() {
+                    return Err(syn::Error::new(name.span(), "this element is not closed"));
+                }
+                children.push(input.parse()?);
+            }
+            input.parse::<Token![<]>()?;
+            input.parse::<Token![/]>()?;
+            let closing = Path::parse_mod_style(input)?;
+            let (expected, found) = (path_name(&name), path_name(&closing));
+            if expected != found {
+                return Err(syn::Error::new(closing.span(), format!("expected `</{}>`, found `</{}>`", expected, found)));
+            }
+            input.parse::<Token![>]>()?;
+        }
+        Ok(Self { name, attributes, child_args, children })
+    }
+}
+
+fn path_name(path: &Path) -> String {
+    path.to_token_stream().to_string().replace(' ', "")
+}
+
+/// The context of the expansion.
+struct Expand<'a> {
+    cx: &'a Expr,
+    builder_args: &'a [Expr],
+}
+
+impl View {
+    pub(crate) fn expand(&self) -> TokenStream {
+        let expand = Expand {
+            cx: &self.cx,
+            builder_args: &self.builder_args,
+        };
+        match &self.root {
+            Child::Element(element) => expand.element(element),
+            Child::Expr(expr) => expr.to_token_stream(),
+            Child::If { .. } | Child::For { .. } => syn::Error::new(
+                Span::call_site(),
+                "the root of a view must be an element or a value",
+            ).into_compile_error(),
+        }
+    }
+}
+
+impl Expand<'_> {
+    /// `Name::builder(args).attribute(value)...` then the children, then `.get(cx)`.
+    fn element(&self, element: &Element) -> TokenStream {
+        let builder = builder_ident();
+        let name = &element.name;
+        let args = self.builder_args;
+        let attributes = element.attributes.iter().map(|Attribute { name, value }| {
+            quote_spanned! {name.span()=> .#name(#value) }
+        });
+        let children = element.children.iter().map(|child| self.child(child));
+        let cx = self.cx;
+        quote! {
+            {
+                let #builder = #name::builder(#(#args),*) #(#attributes)*;
+                #(#children)*
+                #builder.get(#cx)
+            }
+        }
+    }
+
+    /// Statements adding `child` to the builder of the enclosing element.
+    fn child(&self, child: &Child) -> TokenStream {
+        let builder = builder_ident();
+        match child {
+            Child::Element(element) => {
+                let node = self.element(element);
+                let args = element.child_args.iter().flat_map(|args| match args {
+                    // `child_args={(a, b)}` passes two arguments
+                    Expr::Tuple(tuple) => tuple.elems.iter().map(ToTokens::to_token_stream).collect::<Vec<_>>(),
+                    args => vec![args.to_token_stream()],
+                });
+                quote_spanned! {element.name.span()=>
+                    let #builder = #builder.child(#node #(, #args)*);
+                }
+            }
+            Child::Expr(expr) => quote_spanned! {expr.span()=>
+                let #builder = #builder.child(#expr);
+            },
+            Child::If { condition, then, otherwise } => {
+                let then = then.iter().map(|child| self.child(child));
+                let otherwise = otherwise.iter().flatten().map(|child| self.child(child));
+                quote! {
+                    let #builder = if #condition {
+                        #(#then)*
+                        #builder
+                    } else {
+                        #(#otherwise)*
+                        #builder
+                    };
+                }
+            }
+            Child::For { pat, iter, body } => {
+                let body = body.iter().map(|child| self.child(child));
+                quote! {
+                    let #builder = {
+                        let mut #builder = #builder;
+                        for #pat in #iter {
+                            #builder = {
+                                #(#body)*
+                                #builder
+                            };
+                        }
+                        #builder
+                    };
+                }
+            }
+        }
+    }
+}
+
+/// The builder of the element being expanded, not visible to the expressions of the user.
+fn builder_ident() -> Ident {
+    Ident::new("__builder", Span::mixed_site())
+}
@@ -1,68 +1,194 @@
-use std::{rc::{Rc, Weak}, any::Any, cell::RefCell, collections::VecDeque};
-
-use crate::{state_function::StateFunction, LiveValue, LiveLink, LiveValueEmitter};
+use std::{rc::{Rc, Weak}, any::{Any, TypeId}, cell::{Cell, RefCell}, collections::{VecDeque, HashMap}, hash::Hash, panic::Location};
+
+use crate::{state_function::StateFunction, LiveValue, LiveLink, LiveValueEmitter, ListenerGuard, utils::AnyKey, context::{self, ContextScope}, task::TaskHandle, subscription::{self, Subscriptions}};
+
+mod send_link;
+pub use send_link::*;
+mod batch;
+pub use batch::*;
+mod update_loop;
+pub use update_loop::*;
+mod command;
+pub use command::*;
+mod error;
+pub use error::*;
+mod history;
+pub use history::*;
+
+type BuilderFn<State> = Rc<dyn Fn(&State, StateLink<State>)>;
+/// A message returns whether the state has to be rendered again.
+type Message<State> = Box<dyn FnOnce(&mut State) -> ShouldRender>;
+type Origin = &'static Location<'static>;
+type MessageQueue<State> = VecDeque<(Message<State>, Origin)>;
+
+thread_local! {
+    /// The messages sent to the states being created by [`StateManager::new_with_link`], by address.
+    static BUILDING: RefCell<Vec<(*const (), Box<dyn Any>)>> = const { RefCell::new(Vec::new()) };
+}
 
 pub struct StateManagerInner<State> {
     state: RefCell<State>,
-    //builder_fn: RefCell<Option<Rc<dyn Fn(&State, StateLink<State>)>>>, // TODO maybe Rc is not needed
-    builder_fn: RefCell<Rc<dyn Fn(&State, StateLink<State>)>>, // TODO maybe Rc is not needed
-    message_queue: RefCell<VecDeque<Box<dyn FnOnce(&mut State)>>>,
+    //builder_fn: RefCell<Option<BuilderFn<State>>>, // TODO maybe Rc is not needed
+    builder_fn: RefCell<BuilderFn<State>>, // TODO maybe Rc is not needed
+    message_queue: RefCell<MessageQueue<State>>,
+    /// Where the last applied messages have been sent from, the most recent first.
+    origins: RefCell<VecDeque<Origin>>,
     to_rerun: RefCell<bool>,
     in_run: RefCell<bool>,
+    /// The sending end of the channel of the [`SendLink`]s, created on demand.
+    sender: RefCell<Option<std::sync::mpsc::Sender<SendUpdate<State>>>>,
+    /// The tasks started by the commands, aborted with the state.
+    tasks: RefCell<Vec<TaskHandle>>,
+    /// Records the messages, see [`HistoryStateManager`].
+    recorder: RefCell<Option<Rc<dyn Recorder<State>>>>,
+}
+
+impl<State> Drop for StateManagerInner<State> {
+    fn drop(&mut self) {
+        for task in self.tasks.borrow().iter() {
+            task.abort();
+        }
+    }
 }
 
-impl<State> StateManagerInner<State> {
+impl<State: 'static> StateManagerInner<State> {
     fn run(&self, self_link: StateLink<State>) {
-        *self.to_rerun.borrow_mut() = true;
-        if *self.in_run.borrow() {
+        if !*self.in_run.borrow() && is_batching() {
+            // a pending rerun outside of a run means that it has already been deferred
+            if !self.to_rerun.replace(true) {
+                defer(move || {
+                    if let Some(manager) = self_link.state.upgrade() {
+                        manager.run(self_link);
+                    }
+                });
+            }
             return;
-        } else {
+        }
+        self.render(self_link);
+    }
+
+    /// Renders the state, and again while the messages applied after the view ask for it.
+    ///
+    /// Unlike [`run`](StateManagerInner::run), this is not deferred in a [`batch`].
+    fn render(&self, self_link: StateLink<State>) {
+        /// Ends the run, even if the builder or a message panics, so that the state is rendered again by the next update.
+        struct EndRun<'a, State>(&'a StateManagerInner<State>);
+        impl<State> Drop for EndRun<'_, State> {
+            fn drop(&mut self) {
+                *self.0.to_rerun.borrow_mut() = false;
+                *self.0.in_run.borrow_mut() = false;
+            }
+        }
+
+        *self.to_rerun.borrow_mut() = true;
+        if !*self.in_run.borrow() {
             *self.in_run.borrow_mut() = true;
+            let _end_run = EndRun(self);
+            let max_reruns = update_limits().max_reruns;
+            let mut renders = 0;
             while *self.to_rerun.borrow() {
                 *self.to_rerun.borrow_mut() = false;
+                if renders > max_reruns {
+                    self.message_queue.borrow_mut().clear();
+                    report(UpdateLoopError {
+                        kind: UpdateLoopKind::TooManyReruns,
+                        component: std::any::type_name::<State>(),
+                        iterations: renders,
+                        origins: self.origins.borrow().iter().copied().collect(),
+                    });
+                    break;
+                }
+                renders += 1;
                 let build = self.builder_fn.borrow().clone();
-                build(&self.state.borrow(), self_link.clone());
+                build(&self.borrow_state(), self_link.clone());
+                // the messages sent while building (e.g. by effects) could not be applied since the state was borrowed
+                if self.apply_queue() {
+                    *self.to_rerun.borrow_mut() = true;
+                }
             }
-            *self.in_run.borrow_mut() = false;
         }
     }
 
-    fn push_on_queue(&self, message: impl FnOnce(&mut State) + 'static) {
-        const MAX_QUEUE_SIZE: usize = 1000;
-        if self.message_queue.borrow().len() > MAX_QUEUE_SIZE {
-            panic!("Message queue is too big, maybe you have infinite update loop?");
+    #[track_caller]
+    fn push_on_queue(&self, message: impl FnOnce(&mut State) -> ShouldRender + 'static) {
+        let origin = origin();
+        let mut queue = self.message_queue.borrow_mut();
+        if queue.len() >= update_limits().max_queue_len {
+            // the queued messages are kept, only the incoming one is discarded
+            let iterations = queue.len() + 1;
+            let origins = std::iter::once(origin)
+                .chain(queue.iter().rev().map(|(_, origin)| *origin))
+                .take(MAX_ORIGINS)
+                .collect();
+            drop(queue);
+            report(UpdateLoopError {
+                kind: UpdateLoopKind::QueueTooLong,
+                component: std::any::type_name::<State>(),
+                iterations,
+                origins,
+            });
+            return;
         }
-        self.message_queue.borrow_mut().push_back(Box::new(message));
+        queue.push_back((Box::new(message), origin));
     }
 
-    fn run_queue(&self, self_link: StateLink<State>) {
+    /// Applies the queued messages to the state, returns whether any of them requires to render the state again.
+    ///
+    /// If the state is currently borrowed, the messages stay in the queue.
+    fn apply_queue(&self) -> bool {
         if self.message_queue.borrow().is_empty() {
-            return;
+            return false;
         }
 
-        let runned = if let Ok(mut state) = self.state.try_borrow_mut() {
+        if let Ok(mut state) = self.state.try_borrow_mut() {
             let pick = || self.message_queue.borrow_mut().pop_front();
-            while let Some(message) = pick() {
-                message(&mut state);
+            let mut should_render = false;
+            while let Some((message, origin)) = pick() {
+                let mut origins = self.origins.borrow_mut();
+                origins.truncate(MAX_ORIGINS - 1);
+                origins.push_front(origin);
+                drop(origins);
+                let recorder = self.recorder.borrow().clone();
+                if let Some(recorder) = &recorder {
+                    recorder.before(&state);
+                }
+                let render = message(&mut state);
+                if let Some(recorder) = &recorder {
+                    recorder.after(render);
+                }
+                should_render |= render;
             }
-            true
+            should_render
         } else {
             false
-        };
-        if runned {
+        }
+    }
+
+    fn run_queue(&self, self_link: StateLink<State>) {
+        if self.apply_queue() {
             self.run(self_link);
         }
     }
 
     fn on_state<R>(&self, on_state: impl FnOnce(&State) -> R) -> R {
-        let state = self.state.borrow();
+        let state = self.borrow_state();
         on_state(&state)
     }
 
     fn on_mut_state<R>(&self, on_state: impl FnOnce(&mut State) -> R) -> R {
-        let mut state = self.state.borrow_mut();
+        let mut state = self.state.try_borrow_mut().unwrap_or_else(|_| panic!(
+            "the state of `{}` is borrowed while it is being rendered or updated, send an update through its link instead",
+            std::any::type_name::<State>(),
+        ));
         on_state(&mut state)
     }
+
+    fn borrow_state(&self) -> std::cell::Ref<'_, State> {
+        self.state.try_borrow().unwrap_or_else(|_| panic!(
+            "the state of `{}` is rendered while it is being updated, send an update through its link instead",
+            std::any::type_name::<State>(),
+        ))
+    }
 }
 
 pub struct StateManager<State> {
@@ -71,21 +197,57 @@ pub struct StateManager<State> {
 
 impl<State: 'static> StateManager<State> {
     pub fn new(state: State) -> Self {
-        Self {
-            inner: Rc::new(StateManagerInner {
-                state: RefCell::new(state),
-                //builder_fn: RefCell::new(None),
-                builder_fn: RefCell::new(Rc::new(|_, _| {})),
-                message_queue: RefCell::new(VecDeque::new()),
-                to_rerun: RefCell::new(false),
-                in_run: RefCell::new(false),
-            }),
+        Self::new_with_link(|_| state)
+    }
+
+    /// Creates the state with a link to it, e.g. to start a task sending updates.
+    ///
+    /// The state does not exist while `init` runs: the updates it sends synchronously are queued,
+    /// and applied once it has returned. The [`SendLink`]s cannot be created before that.
+    pub fn new_with_link(init: impl FnOnce(StateLink<State>) -> State) -> Self {
+        /// Stops queuing the messages, even if `init` panics.
+        struct Built;
+        impl Drop for Built {
+            fn drop(&mut self) {
+                BUILDING.with(|building| building.borrow_mut().pop());
+            }
         }
+
+        let manager = Self {
+            inner: Rc::new_cyclic(|inner: &Weak<StateManagerInner<State>>| {
+                let queue: Box<dyn Any> = Box::new(MessageQueue::<State>::new());
+                BUILDING.with(|building| building.borrow_mut().push((inner.as_ptr() as *const (), queue)));
+                let built = Built;
+                let state = init(StateLink { state: inner.clone() });
+                let queue = BUILDING.with(|building| {
+                    let mut building = building.borrow_mut();
+                    std::mem::replace(&mut building.last_mut().expect("pushed above").1, Box::new(()))
+                });
+                drop(built);
+                StateManagerInner {
+                    state: RefCell::new(state),
+                    //builder_fn: RefCell::new(None),
+                    builder_fn: RefCell::new(Rc::new(|_, _| {})),
+                    message_queue: RefCell::new(*queue.downcast().expect("the queue of this state")),
+                    origins: RefCell::new(VecDeque::new()),
+                    to_rerun: RefCell::new(false),
+                    in_run: RefCell::new(false),
+                    sender: RefCell::new(None),
+                    tasks: RefCell::new(Vec::new()),
+                    recorder: RefCell::new(None),
+                }
+            }),
+        };
+        // there is no view yet, the first one will render the updated state
+        manager.inner.apply_queue();
+        manager
     }
 
+    /// Sets the function rendering the state, and calls it right away, even in a [`batch`].
     pub fn set_builder(&self, builder: impl Fn(&State, StateLink<State>) + 'static) {
         *self.inner.builder_fn.borrow_mut() = Rc::new(builder);
-        self.inner.run(self.link());
+        // the first view is needed right away, even in a batch
+        self.inner.render(self.link());
     }
 
     pub fn link(&self) -> StateLink<State> {
@@ -110,7 +272,17 @@ impl<State: 'static> StateManager<State> {
 pub struct StateLink<P> {
     state: Weak<StateManagerInner<P>>,
 }
-// TODO An intermediate between this and an mpsc
+
+impl<State> StateLink<State> {
+    /// A link to no state, the updates sent through it are discarded.
+    ///
+    /// This is useful to call the methods of a [`Component`] directly, e.g. in the tests.
+    pub fn dangling() -> Self {
+        Self {
+            state: Weak::new(),
+        }
+    }
+}
 
 impl<P> Clone for StateLink<P> {
     fn clone(&self) -> Self {
@@ -120,15 +292,44 @@ impl<P> Clone for StateLink<P> {
     }
 }
 
-impl<State> StateLink<State> {
+impl<State: 'static> StateLink<State> {
     /// Sends an update to the state.
+    ///
+    /// The state is rendered again right away, unless the update is sent in a [`batch`].
+    #[track_caller]
     pub fn send_update(&self, update: impl FnOnce(&mut State) + 'static) {
-        // TODO call self.set, instead of duplicating the code
+        self.send_update_if(move |state| {
+            update(state);
+            true
+        });
+    }
+
+    /// Like [`send_update`](StateLink::send_update), but the state is rendered again only if `update` returns `true`.
+    #[track_caller]
+    pub fn send_update_if(&self, update: impl FnOnce(&mut State) -> ShouldRender + 'static) {
         if let Some(manager) = self.state.upgrade() {
             manager.push_on_queue(update);
             manager.run_queue(self.clone());
+        } else {
+            // queued if the state is being created, otherwise it has expired and there is no effect
+            self.push_while_building(Box::new(update));
         }
-        // if expired, no effect
+    }
+
+    /// Queues `message` if the state is being created by [`StateManager::new_with_link`].
+    #[track_caller]
+    fn push_while_building(&self, message: Message<State>) {
+        let address = self.state.as_ptr() as *const ();
+        let origin = origin();
+        BUILDING.with(|building| {
+            let mut building = building.borrow_mut();
+            let queue = building.iter_mut()
+                .find(|(building, _)| *building == address)
+                .and_then(|(_, queue)| queue.downcast_mut::<MessageQueue<State>>());
+            if let Some(queue) = queue {
+                queue.push_back((message, origin));
+            }
+        });
     }
 
     // TODO update_eq <- this wan an old idea, maybe it's not beneficial / pointless / impossible
@@ -144,30 +345,62 @@ where
     ///
     /// # Notes
     /// See the source of this function for more details.
+    #[track_caller]
     pub fn send_message(&self, message: State::Message) {
-        self.send_update(|state| {
-            state.on_message(message);
+        let link = self.clone();
+        self.send_update(move |state| {
+            let command = state.on_message(message, link.clone());
+            link.run_command(command);
         });
     }
 }
 
 pub struct FunctionsCacheData {
-    functions: Vec<Rc<dyn Any>>,
+    functions: Vec<CachedFunction>,
     functions_pos: usize,
+    keyed_functions: HashMap<(TypeId, AnyKey), KeyedFunction>,
+}
+
+struct CachedFunction {
+    function: Rc<dyn Any>,
+    /// The subscription to the updates of a live function.
+    subscription: Option<ListenerGuard>,
+}
+
+impl CachedFunction {
+    fn new<SF: StateFunction>(function: SF) -> Self {
+        Self {
+            function: Rc::new(RefCell::new(function)),
+            subscription: None,
+        }
+    }
+}
+
+struct KeyedFunction {
+    cached: CachedFunction,
+    /// Whether the function has been evaluated in the current pass.
+    used: bool,
 }
 
 pub struct FunctionsCache {
     data: RefCell<FunctionsCacheData>,
     live_link: Rc<RefCell<LiveLink>>,
+    context_scope: Rc<ContextScope>,
 }
 
 impl FunctionsCache {
+    /// Creates a new cache.
+    ///
+    /// If the cache is created while another cache is building a function, its [`ContextScope`]
+    /// is a child of the scope of that cache.
     #[must_use]
     pub fn new() -> Self {
         Self {
+            context_scope: Rc::new(ContextScope::new(context::building())),
             data: RefCell::new(FunctionsCacheData {
                 functions: Vec::new(),
                 functions_pos: 0,
+                keyed_functions: HashMap::new(),
             }),
             live_link: Rc::new(RefCell::new(LiveLink::new())),
         }
@@ -180,6 +413,26 @@ impl FunctionsCache {
         emitter
     }
 
+    /// The contexts visible to the functions evaluated through this cache.
+    pub fn context_scope(&self) -> &Rc<ContextScope> {
+        &self.context_scope
+    }
+
+    /// Builds a function, the caches it creates inherit the context of this cache.
+    fn build<SF: StateFunction>(&self, props: SF::Input) -> (SF::Output, SF) {
+        context::build_in(&self.context_scope, || SF::build(props))
+    }
+
+    /// Listens to a live function, the listeners of the cache are told about its updates.
+    fn listen(&self, emitter: &LiveValueEmitter) -> ListenerGuard {
+        emitter.listen({
+            let live_link = self.live_link.clone();
+            move || {
+                live_link.borrow().tell_update();
+            }
+        })
+    }
+
     // TODO get_if_new and get_if_changed
 
     #[must_use]
@@ -187,47 +440,138 @@ impl FunctionsCache {
     where
         SF: StateFunction<Output = LiveValue<T>>,
     {
-        let (value, emitter) = self.eval::<SF>(props).into_tuple();
-        emitter.listen({
-            let live_link = self.live_link.clone();
-            move || {
-                live_link.borrow_mut().tell_update();
-            }
-        });
-        value
+        self.eval_positional::<SF>(props, |output| Some(self.listen(&output.emitter))).value
     }
 
     #[must_use]
     pub fn eval<SF: StateFunction>(&self, props: SF::Input) -> SF::Output {
+        self.eval_positional::<SF>(props, |_| None)
+    }
+
+    fn eval_positional<SF: StateFunction>(
+        &self,
+        props: SF::Input,
+        listen: impl FnOnce(&SF::Output) -> Option<ListenerGuard>,
+    ) -> SF::Output {
         let mut data = self.data.borrow_mut();
         let pos = data.functions_pos;
         let result = if pos < data.functions.len() {
-            let function = data.functions[pos].clone().downcast::<RefCell<SF>>();
-            let function = if let Ok(function) = function {
+            let function = data.functions[pos].function.clone().downcast::<RefCell<SF>>();
+            let result = if let Ok(function) = function {
                 if function.borrow().reuse_with(&props) {
                     function.borrow_mut().changed(props)
                 } else { // TODO avoid this IF nesting and else block code repetition
-                    let (result, component) = SF::build(props);
-                    let component = Rc::new(RefCell::new(component));
-                    data.functions[pos] = component.clone();
+                    let (result, function) = self.build::<SF>(props);
+                    data.functions[pos] = CachedFunction::new(function);
                     result
                 }
             } else {
-                let (result, component) = SF::build(props);
-                let function = Rc::new(RefCell::new(component));
-                data.functions.insert(pos, function.clone());
+                let (result, function) = self.build::<SF>(props);
+                data.functions.insert(pos, CachedFunction::new(function));
                 result
             };
             data.functions_pos += 1;
-            function
+            result
         } else {
-            let (result, function) = SF::build(props);
-            let function = Rc::new(RefCell::new(function));
-            data.functions.push(function.clone());
+            let (result, function) = self.build::<SF>(props);
+            data.functions.push(CachedFunction::new(function));
             data.functions_pos = data.functions.len();
             result
         };
 
+        // replacing the previous subscription, if any
+        data.functions[pos].subscription = listen(&result);
+        result
+    }
+
+    /// Like [`eval_live`](FunctionsCache::eval_live), but the function is identified by `key` instead of its call position.
+    #[must_use]
+    pub fn eval_live_keyed<SF, K, T>(&self, key: K, props: SF::Input) -> T
+    where
+        SF: StateFunction<Output = LiveValue<T>>,
+        K: Hash + Eq + 'static,
+    {
+        self.eval_keyed_with::<SF, K>(key, props, |output| Some(self.listen(&output.emitter))).value
+    }
+
+    /// Evaluates a function identified by `key` instead of its call position.
+    ///
+    /// The state of a keyed function is kept as long as the same key is evaluated in every pass,
+    /// regardless of the order, so this is the method to use for dynamic lists whose items
+    /// can be inserted, removed or reordered. Functions whose key is not evaluated in a pass
+    /// are dropped by [`finish`](FunctionsCache::finish).
+    ///
+    /// Keyed and positional functions do not interfere with each other, and the same key can be
+    /// used with different function types.
+    ///
+    /// # Panics
+    /// Panics if the same key is evaluated twice for the same function type in a single pass.
+    ///
+    /// # Example
+    /// ```
+    /// use regui::{StateFunction, component::FunctionsCache};
+    ///
+    /// struct Counter(i32);
+    ///
+    /// impl StateFunction for Counter {
+    ///     type Input = ();
+    ///     type Output = i32;
+    ///     fn build(_input: ()) -> (i32, Self) {
+    ///         (0, Self(0))
+    ///     }
+    ///     fn changed(&mut self, _input: ()) -> i32 {
+    ///         self.0 += 1;
+    ///         self.0
+    ///     }
+    /// }
+    ///
+    /// let mut cache = FunctionsCache::new();
+    /// let first: Vec<i32> = ["a", "b"].into_iter().map(|key| cache.eval_keyed::<Counter, _>(key, ())).collect();
+    /// cache.finish();
+    /// assert_eq!(first, vec![0, 0]);
+    ///
+    /// // "c" is inserted in front and the others keep their state
+    /// let second: Vec<i32> = ["c", "a", "b"].into_iter().map(|key| cache.eval_keyed::<Counter, _>(key, ())).collect();
+    /// cache.finish();
+    /// assert_eq!(second, vec![0, 1, 1]);
+    /// ```
+    #[must_use]
+    pub fn eval_keyed<SF: StateFunction, K: Hash + Eq + 'static>(&self, key: K, props: SF::Input) -> SF::Output {
+        self.eval_keyed_with::<SF, K>(key, props, |_| None)
+    }
+
+    fn eval_keyed_with<SF: StateFunction, K: Hash + Eq + 'static>(
+        &self,
+        key: K,
+        props: SF::Input,
+        listen: impl FnOnce(&SF::Output) -> Option<ListenerGuard>,
+    ) -> SF::Output {
+        let mut data = self.data.borrow_mut();
+        let key = (TypeId::of::<SF>(), AnyKey::new(key));
+
+        if let Some(entry) = data.keyed_functions.get_mut(&key) {
+            assert!(
+                !entry.used,
+                "the same key has been evaluated twice for `{}` in the same pass",
+                std::any::type_name::<SF>()
+            );
+            entry.used = true;
+            let function = entry.cached.function.clone().downcast::<RefCell<SF>>()
+                .expect("keyed functions are indexed by their type");
+            if function.borrow().reuse_with(&props) {
+                let result = function.borrow_mut().changed(props);
+                entry.cached.subscription = listen(&result);
+                return result;
+            }
+        }
+
+        let (result, function) = self.build::<SF>(props);
+        let mut cached = CachedFunction::new(function);
+        cached.subscription = listen(&result);
+        data.keyed_functions.insert(key, KeyedFunction {
+            cached,
+            used: true,
+        });
         result
     }
 
@@ -237,24 +581,78 @@ impl FunctionsCache {
         let pos = data.functions_pos;
         data.functions.truncate(pos);
         data.functions_pos = 0;
+        data.keyed_functions.retain(|_, function| std::mem::take(&mut function.used));
+    }
+}
+
+impl Default for FunctionsCache {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
+/// Whether a component has to be rendered again, see [`Component::update`].
+pub type ShouldRender = bool;
+
 pub trait Component: Sized + 'static { // TODO remove 'static
     type Props;
     type Out: PartialEq + Clone + 'static;
     type Message;
+    /// Creates the component from the props of its first evaluation.
+    ///
+    /// `link` can be stored or moved into a task to send messages later. The messages sent
+    /// synchronously are handled once the component exists, before its first view.
     #[must_use]
-    fn build(props: Self::Props) -> Self; // TODO maybe link avaliable here
-    fn update(&mut self, _props: Self::Props) {} // TODO maybe link avaliable here
+    fn build(props: Self::Props, link: StateLink<Self>) -> Self;
+
+    /// Called when the parent is rendered again, with the new props.
+    ///
+    /// Return `false` if the new props do not change the output, so that [`view`](Component::view)
+    /// is not called and the previous output is reused.
+    fn update(&mut self, _props: Self::Props, _link: StateLink<Self>) -> ShouldRender {
+        true
+    }
 
     /// Called when a message is sent to the component.
     ///
+    /// The returned [`Command`] is performed after the update, its resulting messages are sent back here.
+    ///
     /// Note that it is not necessary to use this method to update the component state:
     /// you could use [`StateLink::send_update`] instead.
     ///
     /// [`StateLink::send_update`]: StateLink::send_update
-    fn on_message(&mut self, _message: Self::Message) {} // TODO maybe link avaliable here
+    fn on_message(&mut self, _message: Self::Message, _link: StateLink<Self>) -> Command<Self::Message> {
+        Command::none()
+    }
+
+    /// Called once, after the first view.
+    ///
+    /// This is the place to start the timers and the background tasks sending messages to the component.
+    fn on_mount(&mut self, _link: StateLink<Self>) {}
+
+    /// Called when the component is dropped, i.e. when its parent stops evaluating it or when the
+    /// application is closed. The component is not rendered anymore after this call.
+    ///
+    /// The children are unmounted after their parent.
+    fn on_unmount(&mut self) {}
+
+    /// The timers and the external events the component listens to, called after every view.
+    ///
+    /// The subscriptions are identified by their id: a new id starts a subscription and a missing
+    /// one cancels it, so the subscriptions can depend on the state. All of them are cancelled
+    /// when the component is dropped. Their messages are handled by [`on_message`](Component::on_message).
+    fn subscriptions(&self) -> Vec<subscription::Subscription<Self::Message>> {
+        Vec::new()
+    }
+
+    /// How the component is saved between the runs of the application, with the `serde` feature.
+    ///
+    /// The component is restored right after [`build`](Component::build), then saved after the
+    /// views that changed it. See [`persist`](crate::persist).
+    #[cfg(feature = "serde")]
+    fn persistence(_props: &Self::Props) -> Option<crate::persist::Persistence<Self>> {
+        None
+    }
     #[must_use]
     fn view(&self, link: StateLink<Self>, cache: &FunctionsCache) -> Self::Out;
     // TODO reuse with component
@@ -263,95 +661,152 @@ pub trait Component: Sized + 'static { // TODO remove 'static
 pub struct LiveStateComponent<SC: Component> {
     state_manager: Rc<RefCell<StateManager<SC>>>,
     _components_cache: Rc<RefCell<FunctionsCache>>,
-    out: Rc<RefCell<SC::Out>>,
+    _components_subscription: ListenerGuard,
+    /// The output of the last view, `None` until the first one.
+    out: Rc<RefCell<Option<SC::Out>>>,
     live_link: LiveLink,
+    /// Set while the parent is rendering this component, see [`changed`](LiveStateComponent::changed).
+    in_parent_view: Rc<Cell<bool>>,
+    subscriptions: Rc<RefCell<Subscriptions>>,
+}
+
+/// Diffs the subscriptions of `component`, their messages are sent through `link`.
+fn update_subscriptions<SC: Component>(subscriptions: &RefCell<Subscriptions>, component: &SC, link: &StateLink<SC>) {
+    let link = link.clone();
+    subscriptions.borrow_mut().update(component.subscriptions(), Rc::new(move |message| link.send_message(message)));
+}
+
+impl<SC: Component> LiveStateComponent<SC> {
+    fn current_out(&self) -> SC::Out {
+        self.out.borrow().clone().expect("the component is rendered when built")
+    }
+}
+
+impl<SC: Component> Drop for LiveStateComponent<SC> {
+    fn drop(&mut self) {
+        let state_manager = self.state_manager.borrow();
+        // no view after the unmount, even if it sends messages
+        *state_manager.inner.builder_fn.borrow_mut() = Rc::new(|_, _| {});
+        let inner = &state_manager.inner;
+        let unmounted = match inner.state.try_borrow_mut() {
+            Ok(mut component) => {
+                component.on_unmount();
+                true
+            }
+            Err(_) => false,
+        };
+        if !unmounted {
+            // an error boundary drops the component while it renders, it is unmounted once the view returns
+            inner.push_on_queue(|component: &mut SC| {
+                component.on_unmount();
+                false
+            });
+        }
+    }
 }
 
 impl<SC: Component> StateFunction for LiveStateComponent<SC> {
     type Input = SC::Props;
     type Output = LiveValue<SC::Out>;
     fn build(props: Self::Input) -> (Self::Output, Self) {
-
-        let component = SC::build(props);
-        let state_manager = StateManager::<SC>::new(component);
+        #[cfg(feature = "serde")]
+        let persistence = SC::persistence(&props);
+        let state_manager = StateManager::<SC>::new_with_link(|link| SC::build(props, link));
+        #[cfg(feature = "serde")]
+        if let Some(persistence) = &persistence {
+            state_manager.on_mut_state(|component| persistence.restore(component));
+        }
         let components_cache = Rc::new(RefCell::new(FunctionsCache::new()));
-
-        let result = {
-            let result = state_manager.on_state(|component| {
-                component.view(state_manager.link(), &components_cache.borrow_mut())
-            });
-            components_cache.borrow_mut().finish();
-            result
-        };
-        let out = Rc::new(RefCell::new(result.clone()));
-
+        let out = Rc::new(RefCell::new(None));
         let live_link = LiveLink::new();
+        let in_parent_view = Rc::new(Cell::new(true));
+        let subscriptions = Rc::new(RefCell::new(Subscriptions::new()));
 
-        components_cache.borrow_mut().emitter().listen({
+        // the children changed their output
+        let components_subscription = components_cache.borrow_mut().emitter().listen({
             let link = state_manager.link();
             move || {
                 link.send_update(|_| {});
             }
         });
 
-        let state_manager = Rc::new(RefCell::new(state_manager));
-
-        state_manager.borrow_mut().set_builder({
+        // this runs the first view
+        state_manager.set_builder({
             let cache = Rc::downgrade(&components_cache);
             let out = Rc::downgrade(&out);
             let live_link = live_link.clone();
-            //let state_manager = state_manager.clone();
+            let in_parent_view = in_parent_view.clone();
+            let subscriptions = subscriptions.clone();
             move |component, link| {
                 let cache = match cache.upgrade() {
                     Some(cache) => cache,
                     None => return,
                 };
                 let mut cache = cache.borrow_mut();
-                let new_result = {
-                    let result = component.view(link.clone(), &cache);
-                    cache.finish();
-                    result
+                let rendered = out.upgrade().is_some_and(|out| out.borrow().is_some());
+                let scope = cache.context_scope().clone();
+                let new_result = catch_view(std::any::type_name::<SC>(), &scope, rendered, || {
+                    component.view(link.clone(), &cache)
+                });
+                // the functions not reached because of a panic are dropped
+                cache.finish();
+                let Some(new_result) = new_result else {
+                    return;
                 };
+                update_subscriptions(&subscriptions, component, &link);
+                #[cfg(feature = "serde")]
+                if let Some(persistence) = &persistence {
+                    persistence.save(component);
+                }
                 let out = match out.upgrade() {
                     Some(out) => out,
                     None => return,
                 };
-                if new_result != *out.borrow() {
-                    // set new result
-                    *out.borrow_mut() = new_result.clone();
-                    // signal change
-                    live_link.tell_update();
+                if out.borrow().as_ref() != Some(&new_result) {
+                    *out.borrow_mut() = Some(new_result);
+                    // the parent reads the new output from `changed` if it is rendering this component
+                    if !in_parent_view.get() {
+                        live_link.tell_update();
+                    }
                 }
             }
         });
 
-        (
-            live_link.make_live_value(result),
-            Self {
-                state_manager,
-                _components_cache: components_cache,
-                out,
-                live_link,
-            }
-        )
+        // the messages sent while mounting render the component before the parent reads its output
+        state_manager.on_mut_state(|component| component.on_mount(state_manager.link()));
+        in_parent_view.set(false);
+
+        let this = Self {
+            state_manager: Rc::new(RefCell::new(state_manager)),
+            _components_cache: components_cache,
+            _components_subscription: components_subscription,
+            out,
+            live_link,
+            in_parent_view,
+            subscriptions,
+        };
+        (this.live_link.make_live_value(this.current_out()), this)
     }
     fn changed(&mut self, props: Self::Input) -> Self::Output {
-        //self.state_manager.borrow().on_mut_state(|component| {
-        //    component.update(props);
-        //});
+        // the component is rendered once, and only if `update` asks for it.
+        // If it is already rendering (e.g. its own update has caused the parent to render), the update
+        // is applied when its view returns and the parent is notified of the new output.
         let link = self.state_manager.borrow().link();
-        link.send_update(|component| {
-            component.update(props);
+        self.in_parent_view.set(true);
+        link.send_update_if({
+            let link = link.clone();
+            let subscriptions = self.subscriptions.clone();
+            move |component| {
+                let should_render = component.update(props, link.clone());
+                if !should_render {
+                    // the view updates them otherwise, they can depend on the new props
+                    update_subscriptions(&subscriptions, component, &link);
+                }
+                should_render
+            }
         });
+        self.in_parent_view.set(false);
 
-        // TODO when a component internally updates its state, it will use this update function.
-        // this will cause the parent's view function to be called, which will call the child's changed function (this function).
-        // But this function will call update again here, wich will cause the view function to be called again.
-        // This will cause the view function to be called twice, which is not good.
-        // Maybe just removing this call will be enough, since the the component's update will already call the link update function if needed,
-        // but this should be analyzed more in depth.
-        self.state_manager.borrow().link().send_update(|_| {});
-
-        self.live_link.make_live_value(self.out.borrow().clone())
+        self.live_link.make_live_value(self.current_out())
     }
-}
\ No newline at end of file
+}
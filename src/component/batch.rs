@@ -0,0 +1,76 @@
+use std::cell::{Cell, RefCell};
+
+type Deferred = Box<dyn FnOnce()>;
+
+thread_local! {
+    /// How many [`batch`] calls are currently running.
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+    /// The runs of the states updated in the current batch.
+    static DEFERRED: RefCell<Vec<Deferred>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Runs `updates`, then renders once the states updated by it.
+///
+/// Without batching, every update renders its state immediately, so three setters called in a row
+/// cause three views. In a batch the updates are still applied immediately, but the views are
+/// deferred until the outermost batch returns.
+///
+/// The kits run their event callbacks in a batch, so this is only needed for updates sent from elsewhere.
+///
+/// # Example
+/// ```
+/// use std::{rc::Rc, cell::Cell};
+/// use regui::component::{StateManager, batch};
+///
+/// let views = Rc::new(Cell::new(0));
+/// let manager = StateManager::new(0);
+/// manager.set_builder({
+///     let views = views.clone();
+///     move |_, _| views.set(views.get() + 1)
+/// });
+/// assert_eq!(views.get(), 1);
+///
+/// let link = manager.link();
+/// batch(|| {
+///     link.send_update(|value| *value += 1);
+///     link.send_update(|value| *value += 1);
+/// });
+/// assert_eq!(views.get(), 2);
+/// assert_eq!(manager.on_state(|value| *value), 2);
+/// ```
+pub fn batch<R>(updates: impl FnOnce() -> R) -> R {
+    struct Depth;
+    impl Drop for Depth {
+        fn drop(&mut self) {
+            DEPTH.with(|depth| depth.set(depth.get() - 1));
+        }
+    }
+
+    DEPTH.with(|depth| depth.set(depth.get() + 1));
+    let result = {
+        let _depth = Depth;
+        updates()
+    };
+    if !is_batching() {
+        // the runs can update other states (e.g. the parents of a component), they are rendered right away
+        loop {
+            let deferred = DEFERRED.with(|deferred| std::mem::take(&mut *deferred.borrow_mut()));
+            if deferred.is_empty() {
+                break;
+            }
+            for run in deferred {
+                run();
+            }
+        }
+    }
+    result
+}
+
+pub(crate) fn is_batching() -> bool {
+    DEPTH.with(|depth| depth.get() > 0)
+}
+
+/// Runs `run` when the outermost batch returns.
+pub(crate) fn defer(run: impl FnOnce() + 'static) {
+    DEFERRED.with(|deferred| deferred.borrow_mut().push(Box::new(run)));
+}
@@ -0,0 +1,164 @@
+use std::{future::Future, fmt::{self, Debug}, time::Duration};
+
+use crate::task::{self, LocalFuture};
+
+use super::{Component, StateLink};
+
+/// The side effects requested by [`Component::on_message`], performed by the runtime after the update.
+///
+/// The messages resulting from the commands are sent back to the component through its [`StateLink`].
+/// Since commands are plain values, the components can be tested by inspecting the commands
+/// returned by `on_message`, without performing any I/O.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use regui::component::{Command, CommandAction};
+///
+/// enum Message {
+///     Save,
+///     Saved,
+///     HideNotice,
+/// }
+///
+/// fn on_save() -> Command<Message> {
+///     Command::batch([
+///         Command::future(async { /* write the file */ Message::Saved }),
+///         Command::delay(Duration::from_secs(3), Message::HideNotice),
+///     ])
+/// }
+///
+/// let command = on_save();
+/// assert!(matches!(command.actions(), [CommandAction::Future(_), CommandAction::Delay(_, Message::HideNotice)]));
+/// ```
+pub struct Command<M> {
+    actions: Vec<CommandAction<M>>,
+}
+
+/// A single side effect of a [`Command`].
+pub enum CommandAction<M> {
+    /// Send the message right after the update.
+    Message(M),
+    /// Run the future with the spawner of the kit and send its result.
+    Future(LocalFuture<M>),
+    /// Send the message after the duration.
+    Delay(Duration, M),
+}
+
+impl<M> Command<M> {
+    /// No side effect.
+    pub fn none() -> Self {
+        Self { actions: Vec::new() }
+    }
+
+    /// Sends `message` to the component after the update.
+    pub fn message(message: M) -> Self {
+        Self { actions: vec![CommandAction::Message(message)] }
+    }
+
+    /// Runs `future` and sends its output to the component.
+    pub fn future(future: impl Future<Output = M> + 'static) -> Self {
+        Self { actions: vec![CommandAction::Future(Box::pin(future))] }
+    }
+
+    /// Sends `message` to the component after `duration`.
+    pub fn delay(duration: Duration, message: M) -> Self {
+        Self { actions: vec![CommandAction::Delay(duration, message)] }
+    }
+
+    /// Performs all the commands.
+    pub fn batch(commands: impl IntoIterator<Item = Command<M>>) -> Self {
+        Self { actions: commands.into_iter().flat_map(|command| command.actions).collect() }
+    }
+
+    pub fn is_none(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    pub fn actions(&self) -> &[CommandAction<M>] {
+        &self.actions
+    }
+
+    /// Converts the resulting messages, e.g. to forward the commands of a part of the state.
+    pub fn map<N: 'static>(self, f: impl Fn(M) -> N + 'static) -> Command<N>
+    where
+        M: 'static,
+    {
+        let f = std::rc::Rc::new(f);
+        Command {
+            actions: self.actions.into_iter().map(|action| match action {
+                CommandAction::Message(message) => CommandAction::Message(f(message)),
+                CommandAction::Future(future) => {
+                    let f = f.clone();
+                    CommandAction::Future(Box::pin(async move { f(future.await) }))
+                }
+                CommandAction::Delay(duration, message) => CommandAction::Delay(duration, f(message)),
+            }).collect(),
+        }
+    }
+}
+
+impl<M> Default for Command<M> {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl<M> IntoIterator for Command<M> {
+    type Item = CommandAction<M>;
+    type IntoIter = std::vec::IntoIter<CommandAction<M>>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.actions.into_iter()
+    }
+}
+
+impl<M: Debug> Debug for Command<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.actions).finish()
+    }
+}
+
+impl<M: Debug> Debug for CommandAction<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Message(message) => f.debug_tuple("Message").field(message).finish(),
+            Self::Future(_) => f.write_str("Future"),
+            Self::Delay(duration, message) => f.debug_tuple("Delay").field(duration).field(message).finish(),
+        }
+    }
+}
+
+impl<State: Component> StateLink<State> {
+    /// Performs the command returned by [`Component::on_message`].
+    pub(crate) fn run_command(&self, command: Command<State::Message>) {
+        for action in command {
+            match action {
+                CommandAction::Message(message) => self.send_message(message),
+                CommandAction::Future(future) => {
+                    let link = self.clone();
+                    self.spawn(async move {
+                        link.send_message(future.await);
+                    });
+                }
+                CommandAction::Delay(duration, message) => {
+                    let link = self.clone();
+                    let sleep = task::sleep(duration);
+                    self.spawn(async move {
+                        sleep.await;
+                        link.send_message(message);
+                    });
+                }
+            }
+        }
+    }
+
+    /// Spawns a task that is aborted when the state is dropped.
+    fn spawn(&self, future: impl Future<Output = ()> + 'static) {
+        if let Some(manager) = self.state.upgrade() {
+            let handle = task::spawn_local(future);
+            let mut tasks = manager.tasks.borrow_mut();
+            tasks.retain(|task| !task.is_finished());
+            tasks.push(handle);
+        }
+    }
+}
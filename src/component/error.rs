@@ -0,0 +1,100 @@
+use std::{any::Any, fmt::{self, Display}, panic::{self, AssertUnwindSafe}, rc::Rc};
+
+use crate::{context::ContextScope, utils::PtrEqRc};
+
+/// A panic or an error raised by a component while rendering, caught by an
+/// [`ErrorBoundary`](crate::function_component::ErrorBoundary).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentError {
+    /// The type name of the component.
+    pub component: &'static str,
+    /// The panic message or the error formatted with `Display`.
+    pub message: String,
+    /// Whether the component has panicked, as opposed to returning an error.
+    pub panicked: bool,
+}
+
+impl ComponentError {
+    /// An error returned by a component, see [`TryComponentFunction`](crate::function_component::TryComponentFunction).
+    pub fn error(component: &'static str, error: impl Display) -> Self {
+        Self {
+            component,
+            message: error.to_string(),
+            panicked: false,
+        }
+    }
+
+    /// Converts the payload of a panic, keeping it if it already is a `ComponentError`.
+    pub(crate) fn from_panic(component: &'static str, payload: Box<dyn Any + Send>) -> Self {
+        let payload = match payload.downcast::<ComponentError>() {
+            Ok(error) => return *error,
+            Err(payload) => payload,
+        };
+        let message = if let Some(message) = payload.downcast_ref::<&str>() {
+            message.to_string()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            "unknown panic payload".to_string()
+        };
+        Self {
+            component,
+            message,
+            panicked: true,
+        }
+    }
+
+    /// Unwinds to the closest error boundary without calling the panic hook.
+    ///
+    /// Use this only when there is a boundary, otherwise the error would not be printed.
+    pub(crate) fn raise(self) -> ! {
+        panic::resume_unwind(Box::new(self))
+    }
+}
+
+impl Display for ComponentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let what = if self.panicked { "panicked" } else { "failed" };
+        write!(f, "`{}` {}: {}", self.component, what, self.message)
+    }
+}
+
+impl std::error::Error for ComponentError {}
+
+/// Receives the errors of the descendants of an error boundary, provided as a context.
+#[derive(Clone, PartialEq)]
+pub(crate) struct ErrorHandler(pub(crate) PtrEqRc<dyn Fn(ComponentError)>);
+
+impl ErrorHandler {
+    pub(crate) fn new(handle: impl Fn(ComponentError) + 'static) -> Self {
+        Self(PtrEqRc::from(Rc::new(handle) as Rc<dyn Fn(ComponentError)>))
+    }
+
+    /// The handler of the closest boundary enclosing the component owning `scope`.
+    ///
+    /// The scope of a component is the one of its children, so the lookup starts from the parent:
+    /// a boundary does not handle its own errors.
+    pub(crate) fn enclosing(scope: &ContextScope) -> Option<Self> {
+        scope.parent().and_then(|parent| parent.get::<Self>())
+    }
+}
+
+/// Runs the view of a component, catching its panics if an error boundary encloses it.
+///
+/// If the component has an output to keep (`rendered`), the error is handed to the boundary and `None`
+/// is returned. Otherwise the unwinding goes on, up to the first ancestor that can keep its output or to the boundary.
+pub(crate) fn catch_view<R>(component: &'static str, scope: &ContextScope, rendered: bool, view: impl FnOnce() -> R) -> Option<R> {
+    let payload = match panic::catch_unwind(AssertUnwindSafe(view)) {
+        Ok(result) => return Some(result),
+        Err(payload) => payload,
+    };
+    let Some(handler) = ErrorHandler::enclosing(scope) else {
+        panic::resume_unwind(payload);
+    };
+    let error = ComponentError::from_panic(component, payload);
+    if !rendered {
+        error.raise();
+    }
+    (handler.0)(error);
+    None
+}
@@ -0,0 +1,247 @@
+use std::{cell::{Cell, RefCell}, collections::VecDeque, rc::Rc};
+
+use super::{ShouldRender, StateLink, StateManager};
+
+/// Observes the messages applied to a state, see [`HistoryStateManager`].
+pub(crate) trait Recorder<State> {
+    /// Called before a message is applied to `state`.
+    fn before(&self, state: &State);
+    /// Called after the message, with whether it has asked to render the state again.
+    fn after(&self, should_render: ShouldRender);
+}
+
+/// The depth of the histories that have not been given one.
+pub const DEFAULT_HISTORY_DEPTH: usize = 100;
+
+/// A [`StateManager`] recording the updates and the messages sent through its links, to undo them.
+///
+/// Every update applied to the state is an entry of the [`History`], unless it is applied within
+/// a [`transaction`](History::transaction). Undoing or redoing an entry renders the state again.
+/// The updates that do not ask to render the state, e.g. a [`send_update_if`](StateLink::send_update_if)
+/// returning `false`, are not recorded.
+/// The state is cloned before each update, so it should be cheap to clone, e.g. with `Rc`s.
+///
+/// The changes made with [`on_mut_state`](HistoryStateManager::on_mut_state) are not recorded.
+///
+/// # Example
+/// ```
+/// use regui::component::HistoryStateManager;
+///
+/// let manager = HistoryStateManager::new(String::new());
+/// let history = manager.history();
+/// let link = manager.link();
+///
+/// link.send_update(|text| text.push('a'));
+/// history.transaction(|| {
+///     link.send_update(|text| text.push('b'));
+///     link.send_update(|text| text.push('c'));
+/// });
+/// assert_eq!(manager.on_state(String::clone), "abc");
+///
+/// history.undo();
+/// assert_eq!(manager.on_state(String::clone), "a");
+/// history.undo();
+/// assert_eq!(manager.on_state(String::clone), "");
+/// history.redo();
+/// assert_eq!(manager.on_state(String::clone), "a");
+/// ```
+pub struct HistoryStateManager<State: Clone> {
+    manager: StateManager<State>,
+    history: History<State>,
+}
+
+impl<State: Clone + 'static> HistoryStateManager<State> {
+    pub fn new(state: State) -> Self {
+        Self::from_manager(StateManager::new(state))
+    }
+
+    /// See [`StateManager::new_with_link`].
+    pub fn new_with_link(init: impl FnOnce(StateLink<State>) -> State) -> Self {
+        Self::from_manager(StateManager::new_with_link(init))
+    }
+
+    fn from_manager(manager: StateManager<State>) -> Self {
+        let history = History {
+            inner: Rc::new(HistoryInner {
+                link: manager.link(),
+                undo: RefCell::new(VecDeque::new()),
+                redo: RefCell::new(Vec::new()),
+                before: RefCell::new(None),
+                travelling: Cell::new(false),
+                transaction: Cell::new(0),
+                transaction_recorded: Cell::new(false),
+                max_depth: Cell::new(DEFAULT_HISTORY_DEPTH),
+            }),
+        };
+        *manager.inner.recorder.borrow_mut() = Some(history.inner.clone());
+        Self {
+            manager,
+            history,
+        }
+    }
+
+    /// Keeps at most `max_depth` entries to undo, the oldest ones are forgotten.
+    pub fn with_max_depth(self, max_depth: usize) -> Self {
+        self.history.set_max_depth(max_depth);
+        self
+    }
+
+    /// The history of the state, to undo and redo the updates.
+    pub fn history(&self) -> History<State> {
+        self.history.clone()
+    }
+
+    /// See [`StateManager::set_builder`], the builder is called again after undoing or redoing.
+    pub fn set_builder(&self, builder: impl Fn(&State, StateLink<State>) + 'static) {
+        self.manager.set_builder(builder)
+    }
+
+    pub fn link(&self) -> StateLink<State> {
+        self.manager.link()
+    }
+
+    pub fn on_state<R>(&self, on_state: impl FnOnce(&State) -> R) -> R {
+        self.manager.on_state(on_state)
+    }
+
+    /// Changes the state without recording it.
+    pub fn on_mut_state<R>(&self, on_state: impl FnOnce(&mut State) -> R) -> R {
+        self.manager.on_mut_state(on_state)
+    }
+}
+
+struct HistoryInner<State> {
+    link: StateLink<State>,
+    /// The states before the entries, the most recent last.
+    undo: RefCell<VecDeque<State>>,
+    /// The states undone, the most recently undone last.
+    redo: RefCell<Vec<State>>,
+    /// The state before the message being applied.
+    before: RefCell<Option<State>>,
+    /// Whether the message being applied is an undo or a redo.
+    travelling: Cell<bool>,
+    /// How many transactions are open.
+    transaction: Cell<usize>,
+    /// Whether the open transaction already has its entry.
+    transaction_recorded: Cell<bool>,
+    max_depth: Cell<usize>,
+}
+
+impl<State: Clone> Recorder<State> for HistoryInner<State> {
+    fn before(&self, state: &State) {
+        let recorded = self.transaction.get() > 0 && self.transaction_recorded.get();
+        if !recorded {
+            *self.before.borrow_mut() = Some(state.clone());
+        }
+    }
+
+    fn after(&self, should_render: ShouldRender) {
+        let before = self.before.borrow_mut().take();
+        if self.travelling.replace(false) || !should_render {
+            return;
+        }
+        if let Some(before) = before {
+            let mut undo = self.undo.borrow_mut();
+            undo.push_back(before);
+            if undo.len() > self.max_depth.get() {
+                undo.pop_front();
+            }
+            self.redo.borrow_mut().clear();
+            if self.transaction.get() > 0 {
+                self.transaction_recorded.set(true);
+            }
+        }
+    }
+}
+
+/// The undo and redo stacks of a [`HistoryStateManager`].
+///
+/// Clones refer to the same history.
+pub struct History<State> {
+    inner: Rc<HistoryInner<State>>,
+}
+
+impl<State: Clone + 'static> History<State> {
+    /// Restores the state before the last entry, and renders it.
+    ///
+    /// Like the other updates, this is applied once the state is not rendering. Nothing happens
+    /// if there is no entry to undo.
+    pub fn undo(&self) {
+        self.travel(|inner, state| {
+            let previous = inner.undo.borrow_mut().pop_back()?;
+            inner.redo.borrow_mut().push(std::mem::replace(state, previous));
+            Some(())
+        })
+    }
+
+    /// Restores the state before the last [`undo`](History::undo), and renders it.
+    ///
+    /// Any new entry clears what can be redone.
+    pub fn redo(&self) {
+        self.travel(|inner, state| {
+            let next = inner.redo.borrow_mut().pop()?;
+            inner.undo.borrow_mut().push_back(std::mem::replace(state, next));
+            Some(())
+        })
+    }
+
+    /// Sends `travel` as an update that is not recorded, the state is rendered if it has travelled.
+    fn travel(&self, travel: impl FnOnce(&HistoryInner<State>, &mut State) -> Option<()> + 'static) {
+        let history = self.inner.clone();
+        self.inner.link.send_update_if(move |state| -> ShouldRender {
+            history.travelling.set(true);
+            travel(&history, state).is_some()
+        });
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.inner.undo.borrow().is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.inner.redo.borrow().is_empty()
+    }
+
+    /// Records the updates applied while `updates` runs as a single entry.
+    ///
+    /// Transactions can be nested, the outermost one makes the entry.
+    pub fn transaction<R>(&self, updates: impl FnOnce() -> R) -> R {
+        /// Closes the transaction, even if `updates` panics.
+        struct Close<'a, State>(&'a HistoryInner<State>);
+        impl<State> Drop for Close<'_, State> {
+            fn drop(&mut self) {
+                self.0.transaction.set(self.0.transaction.get() - 1);
+            }
+        }
+
+        let inner = &self.inner;
+        if inner.transaction.replace(inner.transaction.get() + 1) == 0 {
+            inner.transaction_recorded.set(false);
+        }
+        let _close = Close(inner);
+        updates()
+    }
+
+    /// Forgets all the entries.
+    pub fn clear(&self) {
+        self.inner.undo.borrow_mut().clear();
+        self.inner.redo.borrow_mut().clear();
+    }
+
+    /// Keeps at most `max_depth` entries to undo, the oldest ones are forgotten.
+    pub fn set_max_depth(&self, max_depth: usize) {
+        self.inner.max_depth.set(max_depth);
+        let mut undo = self.inner.undo.borrow_mut();
+        while undo.len() > max_depth {
+            undo.pop_front();
+        }
+    }
+}
+
+impl<State> Clone for History<State> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use std::{sync::{mpsc::{self, Sender, Receiver}, Arc, Mutex, PoisonError}, cell::RefCell, rc::Rc};
+
+use super::{StateLink, StateManagerInner};
+
+/// Sent updates get the link of the state on the UI thread, since a [`StateLink`] cannot be sent.
+pub(crate) type SendUpdate<State> = Box<dyn FnOnce(&mut State, StateLink<State>) + Send>;
+type Wake = Arc<dyn Fn() + Send + Sync>;
+/// The wake hook of a UI thread, shared with its send links so that they see the hook set later.
+type WakeSlot = Arc<Mutex<Option<Wake>>>;
+
+/// A [`StateLink`] that can be sent to other threads.
+///
+/// The updates are queued and applied on the UI thread, the one that created the link, by
+/// [`drain_sent_updates`]. Kits call it on every tick of their event loop, and
+/// install a [`set_wake`] hook to be woken up when an update is sent.
+///
+/// # Example
+/// ```
+/// use regui::component::{StateManager, drain_sent_updates};
+///
+/// let manager = StateManager::new(0);
+/// let link = manager.link().send_link();
+///
+/// std::thread::spawn(move || {
+///     link.send_update(|value| *value += 42);
+/// }).join().unwrap();
+///
+/// // nothing happens until the UI thread drains the updates
+/// assert_eq!(manager.on_state(|value| *value), 0);
+/// drain_sent_updates();
+/// assert_eq!(manager.on_state(|value| *value), 42);
+/// ```
+pub struct SendLink<State> {
+    sender: Sender<SendUpdate<State>>,
+    wake: WakeSlot,
+}
+
+impl<State> Clone for SendLink<State> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            wake: self.wake.clone(),
+        }
+    }
+}
+
+impl<State> SendLink<State> {
+    /// Sends an update to the state, it will be applied on the UI thread.
+    ///
+    /// If the state has been dropped, the update is discarded when the UI thread drains the updates,
+    /// or right away once it has drained them since the state was dropped.
+    pub fn send_update(&self, update: impl FnOnce(&mut State) + Send + 'static) {
+        self.send(Box::new(move |state, _| update(state)));
+    }
+
+    fn send(&self, update: SendUpdate<State>) {
+        if self.sender.send(update).is_ok() {
+            // not locked while waking, the hook could send another update
+            let wake = self.wake.lock().unwrap_or_else(PoisonError::into_inner).clone();
+            if let Some(wake) = wake {
+                wake();
+            }
+        }
+    }
+}
+
+impl<State> SendLink<State>
+where
+    State: super::Component,
+    State::Message: Send,
+{
+    /// Sends a message to the state, see [`StateLink::send_message`].
+    pub fn send_message(&self, message: State::Message) {
+        self.send(Box::new(|state, link| {
+            let command = state.on_message(message, link.clone());
+            link.run_command(command);
+        }));
+    }
+}
+
+impl<State: 'static> StateLink<State> {
+    /// A link to send updates from other threads.
+    pub fn send_link(&self) -> SendLink<State> {
+        let sender = match self.state.upgrade() {
+            Some(manager) => manager.sender(self),
+            // the updates will be discarded
+            None => mpsc::channel().0,
+        };
+        SendLink {
+            sender,
+            wake: WAKE.with(WakeSlot::clone),
+        }
+    }
+}
+
+impl<State: 'static> StateManagerInner<State> {
+    fn sender(&self, link: &StateLink<State>) -> Sender<SendUpdate<State>> {
+        self.sender.borrow_mut().get_or_insert_with(|| {
+            let (sender, receiver) = mpsc::channel();
+            RECEIVERS.with(|receivers| receivers.borrow_mut().push(Rc::new(SentUpdates {
+                receiver,
+                link: link.clone(),
+            })));
+            sender
+        }).clone()
+    }
+}
+
+/// The receiving end of the send links of a state.
+struct SentUpdates<State> {
+    receiver: Receiver<SendUpdate<State>>,
+    link: StateLink<State>,
+}
+
+trait Drain {
+    /// Applies the received updates, returns whether the state still exists.
+    fn drain(&self) -> bool;
+}
+
+impl<State: 'static> Drain for SentUpdates<State> {
+    fn drain(&self) -> bool {
+        while let Ok(update) = self.receiver.try_recv() {
+            let link = self.link.clone();
+            self.link.send_update(move |state| update(state, link));
+        }
+        self.link.state.strong_count() > 0
+    }
+}
+
+thread_local! {
+    static RECEIVERS: RefCell<Vec<Rc<dyn Drain>>> = const { RefCell::new(Vec::new()) };
+    static WAKE: WakeSlot = Arc::new(Mutex::new(None));
+}
+
+/// Applies the updates sent through the [`SendLink`]s created on this thread.
+///
+/// This is meant to be called by the kits on every tick of their event loop.
+pub fn drain_sent_updates() {
+    // the updates can create new send links, so the registry is not borrowed while draining
+    let receivers = RECEIVERS.with(|receivers| receivers.borrow().clone());
+    let dropped: Vec<_> = receivers.into_iter()
+        .filter(|receiver| !receiver.drain())
+        .collect();
+    if !dropped.is_empty() {
+        RECEIVERS.with(|receivers| receivers.borrow_mut().retain(|receiver| {
+            !dropped.iter().any(|dropped| Rc::ptr_eq(receiver, dropped))
+        }));
+    }
+}
+
+/// Sets the hook called when an update is sent through a [`SendLink`] created on this thread.
+///
+/// Kits with a blocking event loop use it to wake the UI thread up, so that it can call [`drain_sent_updates`].
+/// The send links already created use the new hook too.
+pub fn set_wake(wake: impl Fn() + Send + Sync + 'static) {
+    WAKE.with(|current| *current.lock().unwrap_or_else(PoisonError::into_inner) = Some(Arc::new(wake)));
+}
@@ -0,0 +1,152 @@
+use std::{cell::{Cell, RefCell}, fmt, panic::Location, rc::Rc, error::Error};
+
+/// How many message origins an [`UpdateLoopError`] reports at most.
+pub(crate) const MAX_ORIGINS: usize = 8;
+
+/// The limits used to detect the update loops, see [`set_update_limits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UpdateLimits {
+    /// The maximum number of messages waiting to be applied to a state.
+    pub max_queue_len: usize,
+    /// The maximum number of times a state is rendered again in a row because of the updates sent while rendering.
+    pub max_reruns: usize,
+}
+
+impl Default for UpdateLimits {
+    fn default() -> Self {
+        Self {
+            max_queue_len: 1000,
+            max_reruns: 100,
+        }
+    }
+}
+
+/// Which limit has been exceeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateLoopKind {
+    /// [`UpdateLimits::max_queue_len`] has been exceeded, the incoming message has been discarded.
+    ///
+    /// The messages already queued are still applied.
+    QueueTooLong,
+    /// [`UpdateLimits::max_reruns`] has been exceeded, the rendering has been stopped and the queued messages discarded.
+    TooManyReruns,
+}
+
+/// An update loop has been detected, this is most likely caused by a component that
+/// updates its state every time it is rendered.
+///
+/// The error is reported through the handler set with [`set_update_loop_handler`], the
+/// default one prints it to stderr.
+///
+/// # Example
+/// ```
+/// use std::{rc::Rc, cell::RefCell};
+/// use regui::component::{StateManager, UpdateLoopKind, set_update_loop_handler};
+///
+/// let errors = Rc::new(RefCell::new(Vec::new()));
+/// set_update_loop_handler({
+///     let errors = errors.clone();
+///     move |error| errors.borrow_mut().push(error.clone())
+/// });
+///
+/// let manager = StateManager::new(0);
+/// manager.set_builder(|_, link| {
+///     // every view triggers another view
+///     link.send_update(|value| *value += 1);
+/// });
+///
+/// let errors = errors.borrow();
+/// assert_eq!(errors.len(), 1);
+/// assert_eq!(errors[0].kind, UpdateLoopKind::TooManyReruns);
+/// assert_eq!(errors[0].component, "i32");
+/// assert!(errors[0].origins[0].file().ends_with(".rs"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct UpdateLoopError {
+    pub kind: UpdateLoopKind,
+    /// The type name of the state.
+    pub component: &'static str,
+    /// The number of queued messages or of reruns, depending on [`kind`](UpdateLoopError::kind).
+    pub iterations: usize,
+    /// Where the last messages have been sent from, the most recent first.
+    pub origins: Vec<&'static Location<'static>>,
+}
+
+impl fmt::Display for UpdateLoopError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            UpdateLoopKind::QueueTooLong => write!(f, "{} messages queued for {}", self.iterations, self.component)?,
+            UpdateLoopKind::TooManyReruns => write!(f, "{} rendered {} times in a row", self.component, self.iterations)?,
+        }
+        write!(f, ", maybe there is an infinite update loop?")?;
+        if !self.origins.is_empty() {
+            write!(f, " The last messages have been sent from:")?;
+            for origin in &self.origins {
+                write!(f, "\n    {}", origin)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Error for UpdateLoopError {}
+
+type Handler = Rc<dyn Fn(&UpdateLoopError)>;
+
+thread_local! {
+    static LIMITS: Cell<UpdateLimits> = Cell::new(UpdateLimits::default());
+    static HANDLER: RefCell<Option<Handler>> = const { RefCell::new(None) };
+    /// Overrides the location of the caller as the origin of the messages, see [`with_origin`].
+    static ORIGIN: Cell<Option<&'static Location<'static>>> = const { Cell::new(None) };
+}
+
+/// Sets the limits used to detect the update loops on this thread.
+pub fn set_update_limits(limits: UpdateLimits) {
+    LIMITS.with(|current| current.set(limits));
+}
+
+/// The limits used to detect the update loops on this thread.
+pub fn update_limits() -> UpdateLimits {
+    LIMITS.with(|limits| limits.get())
+}
+
+/// Sets the function called when an update loop is detected on this thread.
+pub fn set_update_loop_handler(handler: impl Fn(&UpdateLoopError) + 'static) {
+    HANDLER.with(|current| *current.borrow_mut() = Some(Rc::new(handler)));
+}
+
+pub(crate) fn report(error: UpdateLoopError) {
+    let handler = HANDLER.with(|handler| handler.borrow().clone());
+    match handler {
+        Some(handler) => handler(&error),
+        None => eprintln!("regui: {}", error),
+    }
+}
+
+/// Runs `send` with `origin` as the origin of the messages it sends.
+///
+/// This is for the APIs sending messages through type-erased closures, where `#[track_caller]` cannot reach.
+/// The outermost origin wins, since it is the closest to the user code.
+pub(crate) fn with_origin<R>(origin: &'static Location<'static>, send: impl FnOnce() -> R) -> R {
+    /// Restores the previous origin, even if `send` panics.
+    struct Restore(Option<&'static Location<'static>>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            ORIGIN.with(|current| current.set(self.0));
+        }
+    }
+
+    let _restore = Restore(ORIGIN.with(|current| current.replace(Some(current.get().unwrap_or(origin)))));
+    send()
+}
+
+/// The origin of a message sent by the caller.
+///
+/// The origin set by [`with_origin`] is consumed, the messages sent while applying this one have their own origin.
+#[track_caller]
+pub(crate) fn origin() -> &'static Location<'static> {
+    match ORIGIN.with(|origin| origin.take()) {
+        Some(origin) => origin,
+        None => Location::caller(),
+    }
+}
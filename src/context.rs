@@ -1,5 +1,192 @@
+use std::{rc::{Rc, Weak}, any::{Any, TypeId}, cell::{Cell, RefCell}, collections::HashMap, marker::PhantomData};
 
+use crate::function_component::{Hook, HookCx};
 
-pub trait Context: Clone + 'static {
+/// A value that can be provided to the descendants of a component.
+///
+/// See [`Cx::provide_context`](crate::function_component::Cx::provide_context).
+pub trait Context: Clone + PartialEq + 'static {
     //type Visitor<'a>: Visitor;
-}
\ No newline at end of file
+}
+
+impl<T: Clone + PartialEq + 'static> Context for T {}
+
+/// The contexts provided to the functions evaluated through a [`FunctionsCache`](crate::component::FunctionsCache).
+///
+/// Scopes are chained: a value is looked up in the scope of the cache first, then in the
+/// scope of the cache that built the component owning it, and so on up to the root.
+pub struct ContextScope {
+    parent: Option<Rc<ContextScope>>,
+    provided: RefCell<HashMap<TypeId, Rc<dyn Any>>>,
+}
+
+impl ContextScope {
+    pub fn new(parent: Option<Rc<ContextScope>>) -> Self {
+        Self {
+            parent,
+            provided: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// The scope of the cache that built the component owning this scope, if any.
+    pub fn parent(&self) -> Option<&Rc<ContextScope>> {
+        self.parent.as_ref()
+    }
+
+    /// The value of type `T` provided in this scope or in the closest ancestor.
+    pub fn get<T: Context>(&self) -> Option<T> {
+        self.provider::<T>().map(|provider| provider.value.borrow().clone())
+    }
+
+    fn provider<T: Context>(&self) -> Option<Rc<Provider<T>>> {
+        let provided = self.provided.borrow().get(&TypeId::of::<T>()).cloned();
+        match provided {
+            Some(provider) => Some(provider.downcast::<Provider<T>>().expect("providers are indexed by their type")),
+            None => self.parent.as_ref().and_then(|parent| parent.provider::<T>()),
+        }
+    }
+}
+
+/// Rerenders a consumer, unless it has read the version of the value it is called with.
+type Consumer = dyn Fn(u64);
+
+pub(crate) struct Provider<T> {
+    value: RefCell<T>,
+    /// Incremented when the value changes.
+    version: Cell<u64>,
+    /// The consumers to rerender when the value changes, called with its version.
+    consumers: RefCell<Vec<Weak<Consumer>>>,
+}
+
+impl<T> Provider<T> {
+    fn notify(&self) {
+        let consumers: Vec<_> = {
+            let mut consumers = self.consumers.borrow_mut();
+            consumers.retain(|consumer| consumer.strong_count() > 0);
+            consumers.iter().filter_map(|consumer| consumer.upgrade()).collect()
+        };
+        for consumer in consumers {
+            consumer(self.version.get());
+        }
+    }
+}
+
+thread_local! {
+    /// The scope of the cache currently building a function, see [`building`].
+    static BUILDING: RefCell<Option<Rc<ContextScope>>> = const { RefCell::new(None) };
+}
+
+/// Runs `build` with `scope` as the scope of the building cache.
+///
+/// This is how the context flows down the tree: [`StateFunction::build`](crate::StateFunction::build) does not
+/// know the cache evaluating it, so the caches created while building take this scope as parent.
+pub(crate) fn build_in<R>(scope: &Rc<ContextScope>, build: impl FnOnce() -> R) -> R {
+    struct Restore(Option<Rc<ContextScope>>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            BUILDING.with(|building| *building.borrow_mut() = self.0.take());
+        }
+    }
+
+    let _restore = Restore(BUILDING.with(|building| building.borrow_mut().replace(scope.clone())));
+    build()
+}
+
+/// The scope of the cache currently building a function, if any.
+pub(crate) fn building() -> Option<Rc<ContextScope>> {
+    BUILDING.with(|building| building.borrow().clone())
+}
+
+pub(crate) struct ProvideContext<T> {
+    pub(crate) scope: Rc<ContextScope>,
+    pub(crate) value: T,
+}
+
+/// A provider registered in a scope, removed from it when the providing component is dropped.
+pub(crate) struct ProviderSlot<T: Context> {
+    scope: Weak<ContextScope>,
+    provider: Rc<Provider<T>>,
+}
+
+impl<T: Context> Drop for ProviderSlot<T> {
+    fn drop(&mut self) {
+        let Some(scope) = self.scope.upgrade() else {
+            return;
+        };
+        let mut provided = scope.provided.borrow_mut();
+        let registered = provided.get(&TypeId::of::<T>())
+            .is_some_and(|provider| std::ptr::addr_eq(Rc::as_ptr(provider), Rc::as_ptr(&self.provider)));
+        if registered {
+            provided.remove(&TypeId::of::<T>());
+        }
+    }
+}
+
+impl<T: Context> Hook for ProvideContext<T> {
+    type Slot = ProviderSlot<T>;
+    type Output = ();
+    fn init(&mut self, _cx: &mut HookCx) -> Self::Slot {
+        let provider = Rc::new(Provider {
+            value: RefCell::new(self.value.clone()),
+            version: Cell::new(0),
+            consumers: RefCell::new(Vec::new()),
+        });
+        self.scope.provided.borrow_mut().insert(TypeId::of::<T>(), provider.clone());
+        ProviderSlot {
+            scope: Rc::downgrade(&self.scope),
+            provider,
+        }
+    }
+    fn call(self, slot: &mut Self::Slot, cx: &mut HookCx) {
+        let provider = &slot.provider;
+        if *provider.value.borrow() == self.value {
+            return;
+        }
+        // the children evaluated in this view already see the new value,
+        // the others are rendered again once the view is done
+        *provider.value.borrow_mut() = self.value;
+        provider.version.set(provider.version.get() + 1);
+        let provider = provider.clone();
+        cx.after_view(move || provider.notify());
+    }
+}
+
+pub(crate) struct UseContext<T> {
+    pub(crate) scope: Rc<ContextScope>,
+    pub(crate) ty: PhantomData<T>,
+}
+
+/// The provider the component is subscribed to, its subscription, and the version of the value it has read.
+type ConsumerSlot<T> = Option<(Rc<Provider<T>>, Rc<Consumer>, Rc<Cell<u64>>)>;
+
+impl<T: Context> Hook for UseContext<T> {
+    type Slot = ConsumerSlot<T>;
+    type Output = Option<T>;
+    fn init(&mut self, _cx: &mut HookCx) -> Self::Slot {
+        None
+    }
+    fn call(self, slot: &mut Self::Slot, cx: &mut HookCx) -> Option<T> {
+        let Some(provider) = self.scope.provider::<T>() else {
+            *slot = None;
+            return None;
+        };
+        let subscribed = matches!(slot, Some((current, _, _)) if Rc::ptr_eq(current, &provider));
+        if !subscribed {
+            let link = cx.link();
+            let read = Rc::new(Cell::new(provider.version.get()));
+            let consumer: Rc<Consumer> = Rc::new({
+                let read = read.clone();
+                // the consumers evaluated by the view of the provider have already read the new value
+                move |version| if read.get() != version {
+                    link.rerender()
+                }
+            });
+            provider.consumers.borrow_mut().push(Rc::downgrade(&consumer));
+            *slot = Some((provider.clone(), consumer, read));
+        }
+        let (_, _, read) = slot.as_ref().expect("subscribed above");
+        read.set(provider.version.get());
+        let value = provider.value.borrow().clone();
+        Some(value)
+    }
+}
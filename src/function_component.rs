@@ -1,6 +1,10 @@
-use std::{rc::Rc, fmt::Debug, any::Any, cell::RefCell};
+use std::{rc::Rc, fmt::Debug, any::Any, cell::RefCell, future::Future, hash::Hash, marker::PhantomData, panic::Location};
 
-use crate::component::{FunctionsCache, Component, StateLink, LiveStateComponent};
+use crate::{component::{FunctionsCache, Component, Command, StateLink, LiveStateComponent, ShouldRender, with_origin}, context::{Context, ProvideContext, UseContext}, signal::{Memo, Signal, ViewTracker}, store::{Reducible, Store}, subscription::Subscription, task::Resource, utils::PtrEqRc};
+
+mod hook; pub use hook::*;
+mod error_boundary; pub use error_boundary::*;
+pub use regui_macros::component;
 
 pub struct Cx<'a, 'b> {
     cache: &'a FunctionsCache,
@@ -19,17 +23,320 @@ impl<'a, 'b> Cx<'a, 'b> {
         self.cache
     }
 
+    #[track_caller]
     pub fn use_state<V: 'static>(&mut self, init: impl FnOnce() -> V) -> UseStateHandle<V> {
         self.state.use_state(init)
     }
 
+    #[track_caller]
     pub fn use_ref<V: 'static>(&mut self, init: impl FnOnce() -> V) -> Rc<V> {
         self.state.use_ref(init)
     }
+
+    /// Runs a custom [`Hook`].
+    #[track_caller]
+    pub fn use_hook<H: Hook>(&mut self, hook: H) -> H::Output {
+        self.state.use_hook(hook)
+    }
+
+    /// Holds a state updated by dispatching actions to `reducer`.
+    ///
+    /// `init` is called on the first view only. Every dispatched action goes through the
+    /// [`StateLink`] of the component, then the component is rendered with the new state.
+    /// The reducer of the last view is used.
+    ///
+    /// # Example
+    /// ```ignore
+    /// enum Action {
+    ///     Increment,
+    ///     Reset,
+    /// }
+    ///
+    /// let counter = cx.use_reducer(|| 0, |count, action| match action {
+    ///     Action::Increment => count + 1,
+    ///     Action::Reset => 0,
+    /// });
+    /// counter.dispatch(Action::Increment);
+    /// ```
+    #[track_caller]
+    pub fn use_reducer<S: 'static, A: 'static>(
+        &mut self,
+        init: impl FnOnce() -> S,
+        reducer: impl Fn(&S, A) -> S + 'static,
+    ) -> UseReducerHandle<S, A> {
+        self.state.use_reducer(init, reducer)
+    }
+
+    /// Computes a value only when `deps` changes.
+    ///
+    /// `compute` is called on the first view and then only when `deps` is different from
+    /// the value it had on the last computation, otherwise the same [`Rc`] is returned.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let visible = cx.use_memo((props.items.clone(), filter.get()), |(items, filter)| {
+    ///     items.iter().filter(|item| item.contains(filter.as_str())).cloned().collect::<Vec<_>>()
+    /// });
+    /// ```
+    #[track_caller]
+    pub fn use_memo<D, V>(&mut self, deps: D, compute: impl FnOnce(&D) -> V) -> Rc<V>
+    where
+        D: PartialEq + 'static,
+        V: 'static,
+    {
+        self.state.use_memo(deps, compute)
+    }
+
+    /// Keeps the same callback as long as `deps` does not change.
+    ///
+    /// Since the returned callback is the same allocation between the views, it compares equal
+    /// and can be passed to the children without making their props change.
+    /// Note that the callback of the last change of `deps` is kept, together with what it captured.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let on_remove: Rc<dyn Fn()> = cx.use_callback(props.id, {
+    ///     let (remove, id) = (props.remove.clone(), props.id);
+    ///     move || remove(id)
+    /// }).into_rc();
+    /// ```
+    #[track_caller]
+    pub fn use_callback<D, F>(&mut self, deps: D, callback: F) -> PtrEqRc<F>
+    where
+        D: PartialEq + 'static,
+        F: 'static,
+    {
+        self.state.use_callback(deps, callback)
+    }
+
+    /// Provides `value` to this component and all its descendants.
+    ///
+    /// The descendants read it with [`use_context`](Cx::use_context), and they are rendered again
+    /// when a different value is provided. Providing a value of the same type again in a
+    /// descendant overrides it for the descendants of that component.
+    ///
+    /// This is a hook: it must be called on every view.
+    ///
+    /// # Example
+    /// ```ignore
+    /// #[derive(Clone, PartialEq)]
+    /// struct Theme {
+    ///     dark: bool,
+    /// }
+    ///
+    /// cx.provide_context(Theme { dark: settings.dark_mode });
+    /// // in any descendant:
+    /// let dark = cx.use_context::<Theme>().map_or(false, |theme| theme.dark);
+    /// ```
+    #[track_caller]
+    pub fn provide_context<T: Context>(&mut self, value: T) {
+        self.state.use_hook(ProvideContext {
+            scope: self.cache.context_scope().clone(),
+            value,
+        })
+    }
+
+    /// The closest value of type `T` provided by this component or an ancestor.
+    ///
+    /// The component is rendered again when the provided value changes.
+    #[track_caller]
+    pub fn use_context<T: Context>(&mut self) -> Option<T> {
+        self.state.use_hook(UseContext {
+            scope: self.cache.context_scope().clone(),
+            ty: PhantomData,
+        })
+    }
+
+    /// Runs `effect` after the view whenever `deps` changes.
+    ///
+    /// The effect is run after the first view and then every time `deps` is different from
+    /// the value it had on the last run. The cleanup function returned by the effect is called
+    /// before the next run and when the component is dropped.
+    ///
+    /// Use `()` as `deps` to run the effect only once.
+    ///
+    /// # Example
+    /// ```ignore
+    /// cx.use_effect(props.interval, |interval| {
+    ///     let task = spawn_local(tick_every(*interval));
+    ///     move || task.abort()
+    /// });
+    /// ```
+    #[track_caller]
+    pub fn use_effect<D, C>(&mut self, deps: D, effect: impl FnOnce(&D) -> C + 'static)
+    where
+        D: PartialEq + 'static,
+        C: FnOnce() + 'static,
+    {
+        self.state.use_effect(deps, effect)
+    }
+
+    /// Runs `mounted` once, after the first view.
+    #[track_caller]
+    pub fn use_mount(&mut self, mounted: impl FnOnce() + 'static) {
+        self.state.use_mount(mounted)
+    }
+
+    /// Runs `unmounted` when the component is dropped, i.e. when its parent stops evaluating it
+    /// or when the application is closed.
+    ///
+    /// The callback passed on the last view is the one called, so it can capture the current values.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let handle = cx.use_ref(|| open_native_handle());
+    /// cx.use_unmount(move || handle.close());
+    /// ```
+    #[track_caller]
+    pub fn use_unmount(&mut self, unmounted: impl FnOnce() + 'static) {
+        self.state.use_unmount(unmounted)
+    }
+
+    /// Runs `future` with the spawner of the kit and returns its state.
+    ///
+    /// The future is started on the first view and every time `deps` changes, the previous task
+    /// is aborted then. The component is rendered again when the future completes, and the task
+    /// is aborted if the component is dropped before. The future passed on the other views is dropped
+    /// without being polled.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let user = cx.use_future(props.user_id, fetch_user(props.user_id));
+    /// let text = match user {
+    ///     Resource::Ready(user) => user.name,
+    ///     _ => "Loading...".into(),
+    /// };
+    /// ```
+    #[track_caller]
+    pub fn use_future<D, T>(&mut self, deps: D, future: impl Future<Output = T> + 'static) -> Resource<T>
+    where
+        D: PartialEq + 'static,
+        T: Clone + 'static,
+    {
+        self.state.use_future(deps, future)
+    }
+
+    /// Like [`use_future`](Cx::use_future), for a future that can fail.
+    #[track_caller]
+    pub fn use_async_resource<D, T, E>(&mut self, deps: D, future: impl Future<Output = Result<T, E>> + 'static) -> Resource<T, E>
+    where
+        D: PartialEq + 'static,
+        T: Clone + 'static,
+        E: Clone + 'static,
+    {
+        self.state.use_async_resource(deps, future)
+    }
+
+    /// Listens to `subscriptions` while the component is alive, `on_message` handles their messages.
+    ///
+    /// Like [`Component::subscriptions`], the subscriptions are diffed by id after every view:
+    /// pass `None` to cancel a subscription. The handler of the last view is the one called.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let seconds = cx.use_state(|| 0);
+    /// cx.use_subscription(running.then(|| Subscription::interval("clock", Duration::from_secs(1), || ())), {
+    ///     let seconds = seconds.clone();
+    ///     move |()| seconds.set(seconds.get() + 1)
+    /// });
+    /// ```
+    #[track_caller]
+    pub fn use_subscription<M: 'static>(
+        &mut self,
+        subscriptions: impl IntoIterator<Item = Subscription<M>>,
+        on_message: impl Fn(M) + 'static,
+    ) {
+        self.state.use_subscription(subscriptions, on_message)
+    }
+
+    /// A [`Signal`] created on the first view.
+    ///
+    /// The component renders again only when a signal read by its view is set, wherever it is
+    /// set from. Pass the signal to the children instead of its value to render only them.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let count = cx.use_signal(|| 0);
+    /// Button::builder()
+    ///     .text(&format!("Clicked {} times", count.get()))
+    ///     .on_click(move || count.update(|count| *count += 1))
+    ///     .get(cx)
+    /// ```
+    #[track_caller]
+    pub fn use_signal<T: 'static>(&mut self, init: impl FnOnce() -> T) -> Signal<T> {
+        self.state.use_signal(init)
+    }
+
+    /// A [`Memo`] created on the first view.
+    ///
+    /// The function of the first view is kept: it should read signals rather than values of the view.
+    #[track_caller]
+    pub fn use_computed<T: PartialEq + 'static>(&mut self, compute: impl Fn() -> T + 'static) -> Memo<T> {
+        self.state.use_computed(compute)
+    }
+
+    /// A state saved between the runs of the application under `key`, with the `serde` feature.
+    ///
+    /// The value is restored from the [storage](crate::persist) on the first view, or created by `init`
+    /// if there is none, then saved after the views that changed it. Each key must be used by a
+    /// single component at a time.
+    ///
+    /// The key of the first view is kept: the value is neither reloaded nor saved under another key
+    /// if `key` changes later. Give the component a new [key](crate::component::FunctionsCache::eval_keyed)
+    /// to restore another value.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let draft = cx.use_persistent_state("message-draft", String::new);
+    /// Entry::builder()
+    ///     .text(&draft.get())
+    ///     .on_changed(move |text| draft.set(text.to_string()))
+    ///     .get(cx)
+    /// ```
+    #[cfg(feature = "serde")]
+    #[track_caller]
+    pub fn use_persistent_state<V>(&mut self, key: impl Into<String>, init: impl FnOnce() -> V) -> UseStateHandle<V>
+    where
+        V: serde::Serialize + serde::de::DeserializeOwned + 'static,
+    {
+        self.state.use_persistent_state(key, init)
+    }
+
+    /// Selects a part of the state of the [`Store`] provided by an ancestor.
+    ///
+    /// The component is rendered again only when the selection changes. The store itself, e.g.
+    /// to dispatch actions, is read with [`use_context`](Cx::use_context).
+    ///
+    /// # Panics
+    /// If no ancestor provides a `Store<S>`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// // in the root component:
+    /// let store = cx.use_ref(|| Store::new(Todos::default()));
+    /// cx.provide_context((*store).clone());
+    ///
+    /// // in any descendant:
+    /// let count = cx.use_store(|todos: &Todos| todos.items.len());
+    /// let store = cx.use_context::<Store<Todos>>().unwrap();
+    /// let add = move || store.dispatch(TodoAction::Add("milk".into()));
+    /// ```
+    #[track_caller]
+    pub fn use_store<S: Reducible, T: Clone + PartialEq + 'static>(&mut self, selector: impl Fn(&S) -> T + 'static) -> T {
+        let Some(store) = self.use_context::<Store<S>>() else {
+            panic!("`use_store` needs an ancestor providing a `Store<{}>`", std::any::type_name::<S>());
+        };
+        self.state.use_hook(UseStore {
+            store,
+            selector,
+        })
+    }
 }
 
 /// Declares a function component
 ///
+/// The [`component`] attribute does the same, reading the names and the types from the function.
+///
 /// # Example
 /// ```ignore
 /// function_component!(pub MyComponent my_component(i32) -> Vec<NwgControlNode>);
@@ -50,160 +357,377 @@ impl<'a, 'b> Cx<'a, 'b> {
 ///     pub MyComponent my_component(i32) -> Vec<NwgControlNode>
 /// );
 /// ```
+///
+/// Prefix the declaration with `memo` to skip the views when the parent passes props equal to
+/// the previous ones, see [`ComponentFunction::props_equal`]. The props must implement `PartialEq`:
+/// ```ignore
+/// function_component!(memo pub MyComponent my_component(i32) -> Vec<NwgControlNode>);
+/// ```
 #[macro_export]
 macro_rules! decl_function_component {
-    ($(#[$outer:meta])* pub $name:ident $func_name:ident ($props:ty) -> $out:ty) => {
+    (@declare [$($vis:tt)*] [$($memo:tt)*] $(#[$outer:meta])* $name:ident $func_name:ident ($props:ty) -> $out:ty) => {
         $(#[$outer])*
-        pub struct $name;
+        $($vis)* struct $name;
         impl $crate::function_component::ComponentFunction for $name {
             type Props = $props;
             type Out = $out;
-            fn call<'a>(props: &Self::Props, cx: &mut $crate::function_component::Cx) -> Self::Out {
+            fn call(props: &Self::Props, cx: &mut $crate::function_component::Cx) -> Self::Out {
                 $func_name(props, cx)
             }
+            $($memo)*
         }
     };
-    ($(#[$outer:meta])* priv $name:ident $func_name:ident ($props:ty) -> $out:ty) => {
-        $(#[$outer])*
-        struct $name;
-        impl $crate::function_component::ComponentFunction for $name {
-            type Props = $props;
-            type Out = $out;
-            fn call<'a>(props: &Self::Props, cx: &mut $crate::function_component::Cx) -> Self::Out {
-                $func_name(props, &mut cx)
-            }
+    (@memo) => {
+        fn props_equal(old: &Self::Props, new: &Self::Props) -> bool {
+            old == new
         }
     };
-    ($(#[$outer:meta])* $name:ident $func_name:ident ($props:ty) -> $out:ty) => {
-        decl_function_component!($(#[$outer])* priv $name $func_name ($props) -> $out);
-    }
+    ($(#[$outer:meta])* memo pub $name:ident $func_name:ident ($props:ty) -> $out:ty) => {
+        $crate::decl_function_component!(@declare [pub] [$crate::decl_function_component!(@memo);] $(#[$outer])* $name $func_name ($props) -> $out);
+    };
+    ($(#[$outer:meta])* memo $(priv)? $name:ident $func_name:ident ($props:ty) -> $out:ty) => {
+        $crate::decl_function_component!(@declare [] [$crate::decl_function_component!(@memo);] $(#[$outer])* $name $func_name ($props) -> $out);
+    };
+    ($(#[$outer:meta])* pub $name:ident $func_name:ident ($props:ty) -> $out:ty) => {
+        $crate::decl_function_component!(@declare [pub] [] $(#[$outer])* $name $func_name ($props) -> $out);
+    };
+    ($(#[$outer:meta])* $(priv)? $name:ident $func_name:ident ($props:ty) -> $out:ty) => {
+        $crate::decl_function_component!(@declare [] [] $(#[$outer])* $name $func_name ($props) -> $out);
+    };
 }
 
 pub trait ComponentFunction: 'static + Sized {
-    type Props;
-    type Out: Clone + PartialEq;
-    fn call<'a>(props: Self::Props, cx: &mut Cx) -> Self::Out;
+    type Props: 'static;
+    type Out: Clone + PartialEq + 'static;
+    fn call(props: &Self::Props, cx: &mut Cx) -> Self::Out;
+
+    /// Whether the new props passed by the parent are equal to the previous ones.
+    ///
+    /// If they are, [`call`](ComponentFunction::call) is skipped and the previous output is reused.
+    /// This is opt-in, the default always calls the component: use the `memo` flavour of
+    /// [`decl_function_component!`] to compare the props with `PartialEq`.
+    fn props_equal(_old: &Self::Props, _new: &Self::Props) -> bool {
+        false
+    }
+
+    /// Evaluates the component as a child of the component owning `cx`.
+    fn eval(cx: &mut Cx, props: Self::Props) -> Self::Out {
+        cx.cache().eval_live::<LiveStateComponent<FunctionComponent<Self>>, Self::Out>(props)
+    }
+
+    /// Like [`eval`](ComponentFunction::eval), but the component is identified by `key` instead of its call position.
+    ///
+    /// See [`FunctionsCache::eval_keyed`].
+    fn eval_keyed<K: Hash + Eq + 'static>(cx: &mut Cx, key: K, props: Self::Props) -> Self::Out {
+        cx.cache().eval_live_keyed::<LiveStateComponent<FunctionComponent<Self>>, K, Self::Out>(key, props)
+    }
 }
 
 pub struct FunctionComponent<F: ComponentFunction> {
     props: F::Props,
     manager: RefCell<StateVeriablesManager>,
+    /// The signals read by the last view.
+    signals: ViewTracker,
 }
 
-impl<F: ComponentFunction> Component for FunctionComponent<F>
-where
-    F::Props: Clone,
-{
+impl<F: ComponentFunction> Component for FunctionComponent<F> {
     type Props = F::Props;
     type Message = ();
     type Out = F::Out;
 
-    fn build(props: Self::Props) -> Self {
+    fn build(props: Self::Props, link: StateLink<Self>) -> Self {
         Self {
             props,
             manager: RefCell::new(StateVeriablesManager::new()),
+            signals: ViewTracker::new(move || link.send_update(|_| {})),
         }
     }
 
-    fn update(&mut self, props: Self::Props) {
+    fn update(&mut self, props: Self::Props, _link: StateLink<Self>) -> ShouldRender {
+        if F::props_equal(&self.props, &props) {
+            return false;
+        }
         self.props = props;
+        true
     }
 
-    fn on_message(&mut self, _message: Self::Message) {
+    fn on_message(&mut self, _message: Self::Message, _link: StateLink<Self>) -> Command<()> {
+        Command::none()
     }
 
     fn view(&self, link: StateLink<Self>, cache: &FunctionsCache) -> Self::Out {
         let mut state = State {
+            component: std::any::type_name::<F>(),
             current_pos: 0,
             manager: &mut self.manager.borrow_mut(),
-            tell_update: Rc::new({
-                let link = link.clone();
-                move || link.send_message(())
-            }),
+            link: HookLink {
+                send_update: Rc::new(move |update| link.send_update(move |_| update())),
+            },
+            effects: Vec::new(),
         };
-        let mut cx = Cx::new(cache, &mut state);
-        F::call(self.props.clone(), &mut cx)
+        let out = self.signals.track(|| F::call(&self.props, &mut Cx::new(cache, &mut state)));
+        state.run_effects();
+        out
     }
 }
 
 pub struct StateVeriablesManager {
-    state_values: Vec<Rc<dyn Any>>,
+    state_values: Vec<HookSlot>,
+    /// Whether a view has called all its hooks, after that the hooks cannot change.
+    complete: bool,
+}
+
+/// The slot of a hook, with where it has been created from to explain the hook order errors.
+struct HookSlot {
+    value: Box<dyn Any>,
+    hook: &'static str,
+    location: &'static Location<'static>,
 }
 
 impl StateVeriablesManager {
     pub fn new() -> Self {
         Self {
             state_values: Vec::new(),
+            complete: false,
         }
     }
 }
 
+/// The name of a hook type, without its path and generic parameters.
+fn hook_name<H>() -> &'static str {
+    let name = std::any::type_name::<H>();
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+impl Default for StateVeriablesManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub struct State<'a> {
+    /// The type name of the component, to explain the hook order errors.
+    component: &'static str,
     current_pos: usize,
     manager: &'a mut StateVeriablesManager,
-    tell_update: Rc<dyn Fn()>,
+    link: HookLink,
+    /// The effects to run after the view.
+    effects: Vec<Box<dyn FnOnce()>>,
 }
 
 impl<'a> State<'a> {
-    pub fn use_state<V: 'static>(&mut self, init: impl FnOnce() -> V) -> UseStateHandle<V> {
-        // TODO this implementation is not very generic
-        // implement the generic hooks and use_reducer, implement use_state in terms of use_reducer and hooks
-
-        let value = if self.current_pos < self.manager.state_values.len() {
-            let value = self.manager.state_values[self.current_pos].clone().downcast::<RefCell<V>>().unwrap();
-            self.current_pos += 1;
-            value
-        } else {
-            let value = Rc::new(RefCell::new(init()));
-            self.manager.state_values.push(value.clone());
-            self.current_pos = self.manager.state_values.len();
-            value
+    /// Runs `hook` on its slot, creating the slot on the first view.
+    #[track_caller]
+    pub fn use_hook<H: Hook>(&mut self, mut hook: H) -> H::Output {
+        let mut cx = HookCx {
+            link: &self.link,
+            effects: &mut self.effects,
         };
 
-        UseStateHandle {
-            value: value.clone(),
-            setter: Rc::new({
-                let tell_update = self.tell_update.clone();
-                let value_ref = value.clone();
-                move |value| {
-                    *value_ref.borrow_mut() = value;
-                    tell_update();
-                }
-            }),
+        let name = hook_name::<H>();
+        let location = Location::caller();
+        if self.current_pos == self.manager.state_values.len() {
+            if self.manager.complete {
+                panic!(
+                    "`{}` called a new hook `{}` at {}: the previous views called {} hooks. \
+                    Hooks must be called in the same order on every view, do not call them conditionally",
+                    self.component, name, location, self.current_pos,
+                );
+            }
+            let slot = hook.init(&mut cx);
+            self.manager.state_values.push(HookSlot {
+                value: Box::new(slot),
+                hook: name,
+                location,
+            });
         }
+
+        let slot = &mut self.manager.state_values[self.current_pos];
+        let value = match slot.value.downcast_mut::<H::Slot>() {
+            Some(value) if slot.hook == name => value,
+            _ => panic!(
+                "hook #{} of `{}` changed between views: it was `{}` called at {}, it is now `{}` called at {}. \
+                Hooks must be called in the same order on every view, do not call them conditionally",
+                self.current_pos, self.component, slot.hook, slot.location, name, location,
+            ),
+        };
+        self.current_pos += 1;
+        hook.call(value, &mut cx)
+    }
+
+    #[track_caller]
+    pub fn use_state<V: 'static>(&mut self, init: impl FnOnce() -> V) -> UseStateHandle<V> {
+        self.use_reducer(init, |_, value| value).into_state_handle()
     }
 
+    #[track_caller]
+    pub fn use_reducer<S: 'static, A: 'static>(
+        &mut self,
+        init: impl FnOnce() -> S,
+        reducer: impl Fn(&S, A) -> S + 'static,
+    ) -> UseReducerHandle<S, A> {
+        self.use_hook(UseReducer {
+            init: Some(init),
+            reducer,
+            action: PhantomData,
+        })
+    }
+
+    #[track_caller]
     pub fn use_ref<V: 'static>(&mut self, init: impl FnOnce() -> V) -> Rc<V> {
-        // TODO this implementation is not very generic
-        // implement the generic hooks and use_reducer, implement use_state in terms of use_reducer and hooks
-
-        let value = if self.current_pos < self.manager.state_values.len() {
-            let value = self.manager.state_values[self.current_pos].clone().downcast::<V>().unwrap();
-            self.current_pos += 1;
-            value
-        } else {
-            let value = Rc::new(init());
-            self.manager.state_values.push(value.clone());
-            self.current_pos = self.manager.state_values.len();
-            value
-        };
+        self.use_hook(UseRef {
+            init: Some(init),
+        })
+    }
+
+    #[track_caller]
+    pub fn use_effect<D, C>(&mut self, deps: D, effect: impl FnOnce(&D) -> C + 'static)
+    where
+        D: PartialEq + 'static,
+        C: FnOnce() + 'static,
+    {
+        self.use_hook(UseEffect {
+            deps,
+            effect,
+        })
+    }
+
+    #[track_caller]
+    pub fn use_memo<D, V>(&mut self, deps: D, compute: impl FnOnce(&D) -> V) -> Rc<V>
+    where
+        D: PartialEq + 'static,
+        V: 'static,
+    {
+        self.use_hook(UseMemo {
+            deps,
+            compute,
+        })
+    }
+
+    #[track_caller]
+    pub fn use_callback<D, F>(&mut self, deps: D, callback: F) -> PtrEqRc<F>
+    where
+        D: PartialEq + 'static,
+        F: 'static,
+    {
+        self.use_memo(deps, move |_| callback).into()
+    }
 
-        value
+    #[track_caller]
+    pub fn use_mount(&mut self, mounted: impl FnOnce() + 'static) {
+        self.use_hook(UseMount {
+            mounted,
+        })
+    }
+
+    #[track_caller]
+    pub fn use_unmount(&mut self, unmounted: impl FnOnce() + 'static) {
+        self.use_hook(UseUnmount {
+            unmounted,
+        })
+    }
+
+    #[track_caller]
+    pub fn use_future<D, T>(&mut self, deps: D, future: impl Future<Output = T> + 'static) -> Resource<T>
+    where
+        D: PartialEq + 'static,
+        T: Clone + 'static,
+    {
+        self.use_async_resource(deps, async move { Ok(future.await) })
+    }
+
+    #[track_caller]
+    pub fn use_async_resource<D, T, E>(&mut self, deps: D, future: impl Future<Output = Result<T, E>> + 'static) -> Resource<T, E>
+    where
+        D: PartialEq + 'static,
+        T: Clone + 'static,
+        E: Clone + 'static,
+    {
+        self.use_hook(UseFuture {
+            deps,
+            future,
+        })
+    }
+
+    #[track_caller]
+    pub fn use_subscription<M: 'static>(
+        &mut self,
+        subscriptions: impl IntoIterator<Item = Subscription<M>>,
+        on_message: impl Fn(M) + 'static,
+    ) {
+        self.use_hook(UseSubscription {
+            subscriptions,
+            on_message,
+        })
+    }
+
+    #[track_caller]
+    pub fn use_signal<T: 'static>(&mut self, init: impl FnOnce() -> T) -> Signal<T> {
+        (*self.use_ref(|| Signal::new(init()))).clone()
+    }
+
+    #[track_caller]
+    pub fn use_computed<T: PartialEq + 'static>(&mut self, compute: impl Fn() -> T + 'static) -> Memo<T> {
+        (*self.use_ref(|| Memo::new(compute))).clone()
+    }
+
+    #[cfg(feature = "serde")]
+    #[track_caller]
+    pub fn use_persistent_state<V>(&mut self, key: impl Into<String>, init: impl FnOnce() -> V) -> UseStateHandle<V>
+    where
+        V: serde::Serialize + serde::de::DeserializeOwned + 'static,
+    {
+        let (saver, restored) = self.use_hook(crate::persist::UsePersistentState {
+            key: key.into(),
+            value: PhantomData,
+        });
+        let handle = self.use_state(|| restored.unwrap_or_else(init));
+        self.effects.push(Box::new({
+            let handle = handle.clone();
+            move || crate::persist::save_state(&saver, &handle)
+        }));
+        handle
+    }
+
+    fn run_effects(&mut self) {
+        for effect in std::mem::take(&mut self.effects) {
+            effect();
+        }
     }
 }
 
 impl<'a> Drop for State<'a> {
     fn drop(&mut self) {
-        assert_eq!(self.current_pos, self.manager.state_values.len());
+        // a panicking view has not reached all its hooks, and it may be caught by an error boundary
+        if std::thread::panicking() {
+            return;
+        }
+        if let Some(missing) = self.manager.state_values.get(self.current_pos) {
+            panic!(
+                "`{}` called {} hooks, the previous views called {}: the first missing hook is `{}` called at {}. \
+                Hooks must be called in the same order on every view, do not call them conditionally",
+                self.component, self.current_pos, self.manager.state_values.len(), missing.hook, missing.location,
+            );
+        }
+        self.manager.complete = true;
     }
 }
 
-#[derive(Clone)]
 pub struct UseStateHandle<V> {
     value: Rc<RefCell<V>>,
     setter: Rc<dyn Fn(V)>,
 }
 
+impl<V> Clone for UseStateHandle<V> {
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+            setter: self.setter.clone(),
+        }
+    }
+}
+
 impl<V: Debug> Debug for UseStateHandle<V> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.value.fmt(f)
@@ -211,8 +735,15 @@ impl<V: Debug> Debug for UseStateHandle<V> {
 }
 
 impl<V> UseStateHandle<V> {
+    /// Replaces the value, then the component is rendered again.
+    ///
+    /// Like the other updates, the new value is sent through the [`StateLink`] of the component:
+    /// it is applied right away from the event handlers, the effects and the tasks, but when `set`
+    /// is called while the component renders, the value only changes once the view has returned.
+    /// [`get`](UseStateHandle::get) returns the previous value until then.
+    #[track_caller]
     pub fn set(&self, value: V) {
-        (self.setter)(value);
+        with_origin(Location::caller(), || (self.setter)(value));
     }
     pub fn get(&self) -> V
     where
@@ -224,12 +755,9 @@ impl<V> UseStateHandle<V> {
         let value = self.value.borrow();
         callback(&*value)
     }
+    /// Changes the value in place, without rendering the component again.
     pub fn on_mut_value<Out>(&self, callback: impl FnOnce(&mut V) -> Out) -> Out {
         let mut value = self.value.borrow_mut();
         callback(&mut *value)
     }
-}
-
-trait Hook {
-    type Out;
 }
\ No newline at end of file
@@ -0,0 +1,183 @@
+use std::{fmt::Display, hash::Hash, marker::PhantomData, panic::{self, AssertUnwindSafe}, rc::Rc};
+
+use crate::{component::{ComponentError, ErrorHandler}, utils::PtrEqRc};
+
+use super::{ComponentFunction, Cx};
+
+/// Renders its child, or a fallback once a descendant has panicked or failed while rendering.
+///
+/// The errors are caught during the views of the child and of all its descendants, including the
+/// renders caused by their own updates, and the errors returned by a [`TryComponentFunction`].
+/// The fallback is rendered until [`Retry::retry`] is called, then the child is built again from scratch.
+///
+/// Without a boundary, a panic while rendering takes the whole application down.
+///
+/// # Example
+/// ```ignore
+/// let out = ErrorBoundary::eval(cx, ErrorBoundaryProps::new(
+///     |cx| Settings::eval(cx, ()),
+///     |error, retry, cx| Button::builder()
+///         .text(&format!("{}, click to retry", error.message))
+///         .on_click(move || retry.retry())
+///         .get(cx),
+/// ));
+/// ```
+pub struct ErrorBoundary<Out>(PhantomData<Out>);
+
+/// Renders the fallback of an [`ErrorBoundary`] from the caught error.
+pub type FallbackFn<Out> = dyn Fn(&ComponentError, Retry, &mut Cx) -> Out;
+
+pub struct ErrorBoundaryProps<Out> {
+    pub child: Rc<dyn Fn(&mut Cx) -> Out>,
+    pub fallback: Rc<FallbackFn<Out>>,
+}
+
+impl<Out> ErrorBoundaryProps<Out> {
+    pub fn new(
+        child: impl Fn(&mut Cx) -> Out + 'static,
+        fallback: impl Fn(&ComponentError, Retry, &mut Cx) -> Out + 'static,
+    ) -> Self {
+        Self {
+            child: Rc::new(child),
+            fallback: Rc::new(fallback),
+        }
+    }
+}
+
+/// Clears the error of an [`ErrorBoundary`], so that it renders its child again.
+#[derive(Clone, PartialEq)]
+pub struct Retry(PtrEqRc<dyn Fn()>);
+
+impl Retry {
+    pub fn retry(&self) {
+        (self.0)()
+    }
+}
+
+impl<Out: Clone + PartialEq + 'static> ComponentFunction for ErrorBoundary<Out> {
+    type Props = ErrorBoundaryProps<Out>;
+    type Out = Out;
+
+    fn call(props: &Self::Props, cx: &mut Cx) -> Out {
+        let error = cx.use_state(|| None::<ComponentError>);
+        let handler = cx.use_ref({
+            let error = error.clone();
+            || ErrorHandler::new(move |caught| error.set(Some(caught)))
+        });
+        cx.provide_context((*handler).clone());
+        let retry = cx.use_ref({
+            let error = error.clone();
+            || Retry(PtrEqRc::from(Rc::new(move || error.set(None)) as Rc<dyn Fn()>))
+        });
+
+        let current = match error.get() {
+            Some(current) => current,
+            None => {
+                // the descendants that had already rendered report their errors to the handler,
+                // the others unwind up to here
+                let child = panic::catch_unwind(AssertUnwindSafe(|| BoundaryChild::eval(cx, props.child.clone())));
+                match child {
+                    Ok(out) => return out,
+                    Err(payload) => {
+                        let caught = ComponentError::from_panic(std::any::type_name::<BoundaryChild<Out>>(), payload);
+                        error.set(Some(caught.clone()));
+                        caught
+                    }
+                }
+            }
+        };
+        BoundaryFallback::eval(cx, (current, (*retry).clone(), PtrEqRc::from(props.fallback.clone())))
+    }
+}
+
+/// The child of an [`ErrorBoundary`], a component on its own so that it has its own hooks.
+struct BoundaryChild<Out>(PhantomData<Out>);
+
+impl<Out: Clone + PartialEq + 'static> ComponentFunction for BoundaryChild<Out> {
+    type Props = Rc<dyn Fn(&mut Cx) -> Out>;
+    type Out = Out;
+
+    fn call(child: &Self::Props, cx: &mut Cx) -> Out {
+        child(cx)
+    }
+}
+
+/// The fallback of an [`ErrorBoundary`], a component on its own so that it has its own hooks.
+struct BoundaryFallback<Out>(PhantomData<Out>);
+
+impl<Out: Clone + PartialEq + 'static> ComponentFunction for BoundaryFallback<Out> {
+    type Props = (ComponentError, Retry, PtrEqRc<FallbackFn<Out>>);
+    type Out = Out;
+
+    fn call((error, retry, fallback): &Self::Props, cx: &mut Cx) -> Out {
+        fallback(error, retry.clone(), cx)
+    }
+}
+
+/// A function component that can fail while rendering.
+///
+/// The errors are handled by the closest [`ErrorBoundary`], like the panics. Without a boundary,
+/// an error panics.
+///
+/// # Example
+/// ```ignore
+/// struct Config;
+///
+/// impl TryComponentFunction for Config {
+///     type Props = PathBuf;
+///     type Out = Node;
+///     type Error = std::io::Error;
+///     fn try_call(path: &PathBuf, cx: &mut Cx) -> Result<Node, std::io::Error> {
+///         let text = std::fs::read_to_string(path)?;
+///         Ok(Label::builder().text(&text).get(cx))
+///     }
+/// }
+///
+/// let config = Config::eval(cx, path);
+/// ```
+pub trait TryComponentFunction: 'static + Sized {
+    type Props: 'static;
+    type Out: Clone + PartialEq + 'static;
+    type Error: Display;
+    fn try_call(props: &Self::Props, cx: &mut Cx) -> Result<Self::Out, Self::Error>;
+
+    /// See [`ComponentFunction::props_equal`].
+    fn props_equal(_old: &Self::Props, _new: &Self::Props) -> bool {
+        false
+    }
+
+    /// Evaluates the component as a child of the component owning `cx`.
+    fn eval(cx: &mut Cx, props: Self::Props) -> Self::Out {
+        Fallible::<Self>::eval(cx, props)
+    }
+
+    /// Like [`eval`](TryComponentFunction::eval), but the component is identified by `key` instead of its call position.
+    fn eval_keyed<K: Hash + Eq + 'static>(cx: &mut Cx, key: K, props: Self::Props) -> Self::Out {
+        Fallible::<Self>::eval_keyed(cx, key, props)
+    }
+}
+
+/// The [`ComponentFunction`] running a [`TryComponentFunction`].
+pub struct Fallible<F>(PhantomData<F>);
+
+impl<F: TryComponentFunction> ComponentFunction for Fallible<F> {
+    type Props = F::Props;
+    type Out = F::Out;
+
+    fn call(props: &Self::Props, cx: &mut Cx) -> Self::Out {
+        match F::try_call(props, cx) {
+            Ok(out) => out,
+            Err(error) => {
+                let error = ComponentError::error(std::any::type_name::<F>(), error);
+                if ErrorHandler::enclosing(cx.cache().context_scope()).is_none() {
+                    panic!("{}", error);
+                }
+                error.raise()
+            }
+        }
+    }
+
+    fn props_equal(old: &Self::Props, new: &Self::Props) -> bool {
+        F::props_equal(old, new)
+    }
+}
@@ -0,0 +1,505 @@
+use std::{rc::Rc, cell::RefCell, fmt::Debug, future::Future, marker::PhantomData, panic::Location};
+
+use crate::{component::with_origin, store::{Reducible, Store}, subscription::{Subscription, Subscriptions}, task::{self, Resource, TaskHandle}};
+
+use super::UseStateHandle;
+
+type Update = Box<dyn FnOnce()>;
+
+/// A hook that owns a slot in the state of a function component.
+///
+/// Implement this trait to define custom hooks, then call them with [`Cx::use_hook`](super::Cx::use_hook).
+/// The slot is created by [`init`](Hook::init) on the first view and then handed to [`call`](Hook::call)
+/// on every view, including the first one.
+///
+/// Like all the hooks, custom hooks must be called in the same order on every view.
+///
+/// # Example
+/// A hook counting the views of the component:
+/// ```
+/// use regui::function_component::{Hook, HookCx};
+///
+/// struct UseViewCount;
+///
+/// impl Hook for UseViewCount {
+///     type Slot = usize;
+///     type Output = usize;
+///     fn init(&mut self, _cx: &mut HookCx) -> usize {
+///         0
+///     }
+///     fn call(self, count: &mut usize, _cx: &mut HookCx) -> usize {
+///         *count += 1;
+///         *count
+///     }
+/// }
+/// ```
+pub trait Hook {
+    /// The value stored between the views.
+    type Slot: 'static;
+
+    /// The value returned to the component.
+    type Output;
+
+    /// Creates the slot, this is called on the first view only.
+    fn init(&mut self, cx: &mut HookCx) -> Self::Slot;
+
+    /// Runs the hook on its slot.
+    fn call(self, slot: &mut Self::Slot, cx: &mut HookCx) -> Self::Output;
+}
+
+/// What a [`Hook`] can access besides its slot.
+pub struct HookCx<'a> {
+    pub(super) link: &'a HookLink,
+    pub(super) effects: &'a mut Vec<Update>,
+}
+
+impl<'a> HookCx<'a> {
+    /// A link to the component owning the hook.
+    pub fn link(&self) -> HookLink {
+        self.link.clone()
+    }
+
+    /// Runs `effect` once the view has returned.
+    pub fn after_view(&mut self, effect: impl FnOnce() + 'static) {
+        self.effects.push(Box::new(effect));
+    }
+}
+
+/// A link to the function component owning a hook.
+///
+/// This is the type-erased equivalent of the [`StateLink`](crate::component::StateLink) of the component.
+#[derive(Clone)]
+pub struct HookLink {
+    pub(super) send_update: Rc<dyn Fn(Update)>,
+}
+
+impl HookLink {
+    /// Runs `update` through the state link of the component, then the component is rendered again.
+    ///
+    /// Like [`StateLink::send_update`](crate::component::StateLink::send_update), the update is
+    /// queued if the component is rendering.
+    #[track_caller]
+    pub fn send_update(&self, update: impl FnOnce() + 'static) {
+        with_origin(Location::caller(), || (self.send_update)(Box::new(update)));
+    }
+
+    /// Renders the component again.
+    #[track_caller]
+    pub fn rerender(&self) {
+        self.send_update(|| {});
+    }
+}
+
+pub(super) struct UseRef<F> {
+    pub(super) init: Option<F>,
+}
+
+impl<V: 'static, F: FnOnce() -> V> Hook for UseRef<F> {
+    type Slot = Rc<V>;
+    type Output = Rc<V>;
+    fn init(&mut self, _cx: &mut HookCx) -> Rc<V> {
+        Rc::new((self.init.take().expect("init is called once"))())
+    }
+    fn call(self, slot: &mut Rc<V>, _cx: &mut HookCx) -> Rc<V> {
+        slot.clone()
+    }
+}
+
+pub(super) struct UseReducer<I, R, A> {
+    pub(super) init: Option<I>,
+    pub(super) reducer: R,
+    pub(super) action: PhantomData<fn(A)>,
+}
+
+type Reducer<S, A> = Rc<dyn Fn(&S, A) -> S>;
+
+pub(super) struct ReducerSlot<S, A> {
+    state: Rc<RefCell<S>>,
+    /// The reducer of the last view.
+    reducer: Rc<RefCell<Option<Reducer<S, A>>>>,
+}
+
+impl<S, A, I, R> Hook for UseReducer<I, R, A>
+where
+    S: 'static,
+    A: 'static,
+    I: FnOnce() -> S,
+    R: Fn(&S, A) -> S + 'static,
+{
+    type Slot = ReducerSlot<S, A>;
+    type Output = UseReducerHandle<S, A>;
+    fn init(&mut self, _cx: &mut HookCx) -> Self::Slot {
+        ReducerSlot {
+            state: Rc::new(RefCell::new((self.init.take().expect("init is called once"))())),
+            reducer: Rc::new(RefCell::new(None)),
+        }
+    }
+    fn call(self, slot: &mut Self::Slot, cx: &mut HookCx) -> Self::Output {
+        *slot.reducer.borrow_mut() = Some(Rc::new(self.reducer));
+        UseReducerHandle {
+            state: slot.state.clone(),
+            dispatch: Rc::new({
+                let link = cx.link();
+                let state = slot.state.clone();
+                let reducer = slot.reducer.clone();
+                move |action| {
+                    let state = state.clone();
+                    let reducer = reducer.clone();
+                    link.send_update(move || {
+                        let reducer = reducer.borrow().clone().expect("the reducer is set by the view");
+                        let new_state = reducer(&state.borrow(), action);
+                        *state.borrow_mut() = new_state;
+                    });
+                }
+            }),
+        }
+    }
+}
+
+/// The handle returned by [`Cx::use_reducer`](super::Cx::use_reducer).
+pub struct UseReducerHandle<S, A> {
+    state: Rc<RefCell<S>>,
+    dispatch: Rc<dyn Fn(A)>,
+}
+
+impl<S, A> Clone for UseReducerHandle<S, A> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            dispatch: self.dispatch.clone(),
+        }
+    }
+}
+
+impl<S: Debug, A> Debug for UseReducerHandle<S, A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.state.fmt(f)
+    }
+}
+
+impl<S, A> UseReducerHandle<S, A> {
+    /// Sends `action` to the reducer, the component is then rendered with the new state.
+    #[track_caller]
+    pub fn dispatch(&self, action: A) {
+        with_origin(Location::caller(), || (self.dispatch)(action));
+    }
+    /// The dispatch function alone, to be passed to the children.
+    pub fn dispatcher(&self) -> Rc<dyn Fn(A)> {
+        self.dispatch.clone()
+    }
+    pub fn get(&self) -> S
+    where
+        S: Clone,
+    {
+        self.state.borrow().clone()
+    }
+    pub fn on_value<Out>(&self, callback: impl FnOnce(&S) -> Out) -> Out {
+        let state = self.state.borrow();
+        callback(&*state)
+    }
+}
+
+impl<S> UseReducerHandle<S, S> {
+    /// `use_state` is a reducer replacing the state with the action.
+    pub(super) fn into_state_handle(self) -> UseStateHandle<S> {
+        UseStateHandle {
+            value: self.state,
+            setter: self.dispatch,
+        }
+    }
+}
+
+pub(super) struct UseMemo<D, C> {
+    pub(super) deps: D,
+    pub(super) compute: C,
+}
+
+impl<D, V, C> Hook for UseMemo<D, C>
+where
+    D: PartialEq + 'static,
+    V: 'static,
+    C: FnOnce(&D) -> V,
+{
+    /// The dependencies and the value of the last computation.
+    type Slot = Option<(D, Rc<V>)>;
+    type Output = Rc<V>;
+    fn init(&mut self, _cx: &mut HookCx) -> Self::Slot {
+        None
+    }
+    fn call(self, slot: &mut Self::Slot, _cx: &mut HookCx) -> Rc<V> {
+        if let Some((deps, value)) = slot {
+            if *deps == self.deps {
+                return value.clone();
+            }
+        }
+        let value = Rc::new((self.compute)(&self.deps));
+        *slot = Some((self.deps, value.clone()));
+        value
+    }
+}
+
+pub(super) struct UseEffect<D, E> {
+    pub(super) deps: D,
+    pub(super) effect: E,
+}
+
+pub(super) struct EffectSlot<D> {
+    /// The dependencies of the last run, `None` if the effect has never run.
+    deps: Option<D>,
+    cleanup: Option<Box<dyn FnOnce()>>,
+}
+
+impl<D> Drop for EffectSlot<D> {
+    fn drop(&mut self) {
+        if let Some(cleanup) = self.cleanup.take() {
+            cleanup();
+        }
+    }
+}
+
+impl<D, E, C> Hook for UseEffect<D, E>
+where
+    D: PartialEq + 'static,
+    E: FnOnce(&D) -> C + 'static,
+    C: FnOnce() + 'static,
+{
+    type Slot = Rc<RefCell<EffectSlot<D>>>;
+    type Output = ();
+    fn init(&mut self, _cx: &mut HookCx) -> Self::Slot {
+        Rc::new(RefCell::new(EffectSlot {
+            deps: None,
+            cleanup: None,
+        }))
+    }
+    fn call(self, slot: &mut Self::Slot, cx: &mut HookCx) {
+        if slot.borrow().deps.as_ref() == Some(&self.deps) {
+            return;
+        }
+
+        let slot = slot.clone();
+        cx.after_view(move || {
+            let cleanup = slot.borrow_mut().cleanup.take();
+            if let Some(cleanup) = cleanup {
+                cleanup();
+            }
+            let cleanup = (self.effect)(&self.deps);
+            let mut slot = slot.borrow_mut();
+            slot.deps = Some(self.deps);
+            slot.cleanup = Some(Box::new(cleanup));
+        });
+    }
+}
+
+pub(super) struct UseMount<F> {
+    pub(super) mounted: F,
+}
+
+impl<F: FnOnce() + 'static> Hook for UseMount<F> {
+    /// Whether the component has been mounted.
+    type Slot = bool;
+    type Output = ();
+    fn init(&mut self, _cx: &mut HookCx) -> bool {
+        false
+    }
+    fn call(self, mounted: &mut bool, cx: &mut HookCx) {
+        if !std::mem::replace(mounted, true) {
+            cx.after_view(self.mounted);
+        }
+    }
+}
+
+pub(super) struct UseUnmount<F> {
+    pub(super) unmounted: F,
+}
+
+/// Calls the callback of the last view when the component is dropped.
+pub(super) struct UnmountSlot(Option<Box<dyn FnOnce()>>);
+
+impl Drop for UnmountSlot {
+    fn drop(&mut self) {
+        if let Some(unmounted) = self.0.take() {
+            unmounted();
+        }
+    }
+}
+
+impl<F: FnOnce() + 'static> Hook for UseUnmount<F> {
+    type Slot = UnmountSlot;
+    type Output = ();
+    fn init(&mut self, _cx: &mut HookCx) -> UnmountSlot {
+        UnmountSlot(None)
+    }
+    fn call(self, slot: &mut UnmountSlot, _cx: &mut HookCx) {
+        slot.0 = Some(Box::new(self.unmounted));
+    }
+}
+
+pub(super) struct UseSubscription<S, H> {
+    pub(super) subscriptions: S,
+    pub(super) on_message: H,
+}
+
+type MessageHandler<M> = Rc<dyn Fn(M)>;
+
+pub(super) struct SubscriptionSlot<M> {
+    subscriptions: Subscriptions,
+    /// The handler of the last view.
+    on_message: Rc<RefCell<MessageHandler<M>>>,
+}
+
+impl<M, S, H> Hook for UseSubscription<S, H>
+where
+    M: 'static,
+    S: IntoIterator<Item = Subscription<M>>,
+    H: Fn(M) + 'static,
+{
+    type Slot = Rc<RefCell<SubscriptionSlot<M>>>;
+    type Output = ();
+    fn init(&mut self, _cx: &mut HookCx) -> Self::Slot {
+        Rc::new(RefCell::new(SubscriptionSlot {
+            subscriptions: Subscriptions::new(),
+            on_message: Rc::new(RefCell::new(Rc::new(|_| {}))),
+        }))
+    }
+    fn call(self, slot: &mut Self::Slot, cx: &mut HookCx) {
+        let subscriptions: Vec<_> = self.subscriptions.into_iter().collect();
+        let slot = slot.clone();
+        cx.after_view(move || {
+            let mut slot = slot.borrow_mut();
+            *slot.on_message.borrow_mut() = Rc::new(self.on_message);
+            let on_message = slot.on_message.clone();
+            slot.subscriptions.update(subscriptions, Rc::new(move |message| {
+                // the handler can be replaced while it runs
+                let on_message = on_message.borrow().clone();
+                on_message(message);
+            }));
+        });
+    }
+}
+
+pub(super) struct UseFuture<D, F> {
+    pub(super) deps: D,
+    pub(super) future: F,
+}
+
+pub(super) struct FutureSlot<D, T, E> {
+    /// The dependencies of the running or completed task.
+    deps: Option<D>,
+    value: Resource<T, E>,
+    task: Option<TaskHandle>,
+    /// Identifies the last task, so that a stale result is never stored.
+    generation: usize,
+}
+
+impl<D, T, E> Drop for FutureSlot<D, T, E> {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
+
+impl<D, F, T, E> Hook for UseFuture<D, F>
+where
+    D: PartialEq + 'static,
+    F: Future<Output = Result<T, E>> + 'static,
+    T: Clone + 'static,
+    E: Clone + 'static,
+{
+    type Slot = Rc<RefCell<FutureSlot<D, T, E>>>;
+    type Output = Resource<T, E>;
+    fn init(&mut self, _cx: &mut HookCx) -> Self::Slot {
+        Rc::new(RefCell::new(FutureSlot {
+            deps: None,
+            value: Resource::Pending,
+            task: None,
+            generation: 0,
+        }))
+    }
+    fn call(self, slot: &mut Self::Slot, cx: &mut HookCx) -> Resource<T, E> {
+        let mut current = slot.borrow_mut();
+        if current.deps.as_ref() == Some(&self.deps) {
+            return current.value.clone();
+        }
+
+        if let Some(task) = current.task.take() {
+            task.abort();
+        }
+        current.generation += 1;
+        current.deps = Some(self.deps);
+        current.value = Resource::Pending;
+
+        let generation = current.generation;
+        let weak = Rc::downgrade(slot);
+        let link = cx.link();
+        let future = self.future;
+        current.task = Some(task::spawn_local(async move {
+            let result = future.await;
+            link.send_update(move || {
+                if let Some(slot) = weak.upgrade() {
+                    let mut slot = slot.borrow_mut();
+                    if slot.generation == generation {
+                        slot.value = result.into();
+                        slot.task = None;
+                    }
+                }
+            });
+        }));
+        Resource::Pending
+    }
+}
+
+pub(super) struct UseStore<S: Reducible, F> {
+    pub(super) store: Store<S>,
+    pub(super) selector: F,
+}
+
+type Selector<S, T> = Rc<dyn Fn(&S) -> T>;
+
+pub(super) struct StoreSlot<S: Reducible, T> {
+    /// The store listened to, the provided store can change.
+    listening: Option<(Store<S>, crate::ListenerGuard)>,
+    /// The selection of the last view or of the last change.
+    selected: Rc<RefCell<Option<T>>>,
+    /// The selector of the last view.
+    selector: Rc<RefCell<Option<Selector<S, T>>>>,
+}
+
+impl<S, T, F> Hook for UseStore<S, F>
+where
+    S: Reducible,
+    T: Clone + PartialEq + 'static,
+    F: Fn(&S) -> T + 'static,
+{
+    type Slot = StoreSlot<S, T>;
+    type Output = T;
+    fn init(&mut self, _cx: &mut HookCx) -> Self::Slot {
+        StoreSlot {
+            listening: None,
+            selected: Rc::new(RefCell::new(None)),
+            selector: Rc::new(RefCell::new(None)),
+        }
+    }
+    fn call(self, slot: &mut Self::Slot, cx: &mut HookCx) -> T {
+        let value = self.store.with(&self.selector);
+        *slot.selected.borrow_mut() = Some(value.clone());
+        *slot.selector.borrow_mut() = Some(Rc::new(self.selector));
+
+        if slot.listening.as_ref().is_none_or(|(store, _)| *store != self.store) {
+            let (selected, selector, link) = (slot.selected.clone(), slot.selector.clone(), cx.link());
+            let subscription = self.store.listen(move |state| {
+                let Some(selector) = selector.borrow().clone() else {
+                    return;
+                };
+                let value = selector(state);
+                let changed = selected.borrow().as_ref() != Some(&value);
+                if changed {
+                    *selected.borrow_mut() = Some(value);
+                    link.rerender();
+                }
+            });
+            slot.listening = Some((self.store, subscription));
+        }
+        value
+    }
+}
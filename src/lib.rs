@@ -21,4 +21,15 @@ mod state_function; pub use state_function::*; // TODO better
 pub mod context;
 pub mod component;
 pub mod function_component;
-pub mod utils;
\ No newline at end of file
+#[cfg(feature = "serde")]
+pub mod persist;
+pub mod signal;
+pub mod store;
+pub mod subscription;
+pub mod task;
+pub mod utils;
+
+pub use regui_macros::{view, view as rsx};
+
+// `#[component]` refers to this crate as `::regui`, inside it too
+extern crate self as regui;
\ No newline at end of file
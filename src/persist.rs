@@ -0,0 +1,400 @@
+//! Saves states between the runs of the application, with the `serde` feature.
+//!
+//! The window states, the drafts and the preferences are saved to the [`StorageBackend`] of the
+//! thread, set with [`set_storage_backend`]. By default, they are saved as JSON files in the
+//! configuration directory of the user, see [`FileStorage::in_config_dir`].
+//!
+//! - Function components use [`Cx::use_persistent_state`](crate::function_component::Cx::use_persistent_state),
+//!   a state restored on the first view.
+//! - Components return a [`Persistence`] from [`Component::persistence`](crate::component::Component::persistence),
+//!   they are restored right after [`build`](crate::component::Component::build).
+//!
+//! The values are saved after the views that changed them. The values that cannot be restored,
+//! e.g. because their type has changed, are replaced by the initial ones, and the error is
+//! reported through the handler set with [`set_persist_error_handler`], if any.
+//!
+//! # Example
+//! ```ignore
+//! // in the tests, nothing is written to the disk
+//! persist::set_storage_backend(MemoryStorage::new());
+//!
+//! // in a function component, the width of the previous run
+//! let width = cx.use_persistent_state("sidebar-width", || 200);
+//! ```
+
+use std::{cell::RefCell, collections::HashMap, error::Error, fmt, fs, io, marker::PhantomData, path::PathBuf, rc::Rc};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::function_component::{Hook, HookCx, UseStateHandle};
+
+/// How the values are written to a [`StorageBackend`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Json,
+    Ron,
+}
+
+impl Format {
+    /// The extension of the files of this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Ron => "ron",
+        }
+    }
+
+    pub fn serialize<T: Serialize>(self, value: &T) -> Result<String, String> {
+        match self {
+            Format::Json => serde_json::to_string_pretty(value).map_err(|error| error.to_string()),
+            Format::Ron => ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()).map_err(|error| error.to_string()),
+        }
+    }
+
+    pub fn deserialize<T: DeserializeOwned>(self, text: &str) -> Result<T, String> {
+        match self {
+            Format::Json => serde_json::from_str(text).map_err(|error| error.to_string()),
+            Format::Ron => ron::from_str(text).map_err(|error| error.to_string()),
+        }
+    }
+}
+
+/// Where the persisted values are stored, by key.
+pub trait StorageBackend {
+    /// The format of the stored values.
+    fn format(&self) -> Format {
+        Format::Json
+    }
+
+    /// The value stored for `key`, `None` if there is none.
+    fn load(&self, key: &str) -> io::Result<Option<String>>;
+
+    fn save(&self, key: &str, value: &str) -> io::Result<()>;
+}
+
+/// Stores every value in its own file of a directory, named after its key.
+///
+/// The key `window/main` is stored in `window%2Fmain.json` with [`Format::Json`].
+#[derive(Debug, Clone)]
+pub struct FileStorage {
+    dir: PathBuf,
+    format: Format,
+}
+
+impl FileStorage {
+    /// Stores the values in `dir`, created when the first value is saved.
+    pub fn new(dir: impl Into<PathBuf>, format: Format) -> Self {
+        Self {
+            dir: dir.into(),
+            format,
+        }
+    }
+
+    /// Stores the values in the `app` directory of the configuration directory of the user:
+    /// `%APPDATA%` on Windows, `$XDG_CONFIG_HOME` or `~/.config` elsewhere.
+    ///
+    /// Without such a directory, the values are stored in the `app` directory of the current one.
+    pub fn in_config_dir(app: &str, format: Format) -> Self {
+        let var = |name| std::env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+        let config = if cfg!(windows) {
+            var("APPDATA")
+        } else {
+            var("XDG_CONFIG_HOME").or_else(|| var("HOME").map(|home| home.join(".config")))
+        };
+        Self::new(config.unwrap_or_default().join(app), format)
+    }
+
+    /// The file of `key`.
+    ///
+    /// The bytes other than the ASCII letters, digits, `-`, `_` and `.` are percent-encoded, so that
+    /// a key cannot escape the directory and the different keys have different files.
+    fn path(&self, key: &str) -> PathBuf {
+        let mut name = String::with_capacity(key.len());
+        for byte in key.bytes() {
+            if byte.is_ascii_alphanumeric() || b"-_.".contains(&byte) {
+                name.push(byte as char);
+            } else {
+                name.push_str(&format!("%{:02X}", byte));
+            }
+        }
+        self.dir.join(format!("{}.{}", name, self.format.extension()))
+    }
+}
+
+impl StorageBackend for FileStorage {
+    fn format(&self) -> Format {
+        self.format
+    }
+
+    fn load(&self, key: &str) -> io::Result<Option<String>> {
+        match fs::read_to_string(self.path(key)) {
+            Ok(text) => Ok(Some(text)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn save(&self, key: &str, value: &str) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        // a crash while writing leaves the previous file
+        let path = self.path(key);
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, value)?;
+        fs::rename(temporary, path)
+    }
+}
+
+/// Stores the values in memory, e.g. for the tests.
+///
+/// Clones share the same values.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    values: Rc<RefCell<HashMap<String, String>>>,
+    format: Format,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_format(format: Format) -> Self {
+        Self {
+            values: Default::default(),
+            format,
+        }
+    }
+
+    /// The value stored for `key`.
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.values.borrow().get(key).cloned()
+    }
+
+    /// Stores `value` for `key`, e.g. to prepare a test.
+    pub fn insert(&self, key: impl Into<String>, value: impl Into<String>) {
+        self.values.borrow_mut().insert(key.into(), value.into());
+    }
+}
+
+impl StorageBackend for MemoryStorage {
+    fn format(&self) -> Format {
+        self.format
+    }
+
+    fn load(&self, key: &str) -> io::Result<Option<String>> {
+        Ok(self.get(key))
+    }
+
+    fn save(&self, key: &str, value: &str) -> io::Result<()> {
+        self.insert(key, value);
+        Ok(())
+    }
+}
+
+/// What went wrong with a persisted value.
+#[derive(Debug)]
+pub enum PersistErrorKind {
+    Io(io::Error),
+    Serialize(String),
+    /// The stored value cannot be read back, the initial value is used instead.
+    Deserialize(String),
+}
+
+/// A value could not be saved or restored, see [`set_persist_error_handler`].
+#[derive(Debug)]
+pub struct PersistError {
+    pub key: String,
+    pub kind: PersistErrorKind,
+}
+
+impl fmt::Display for PersistError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            PersistErrorKind::Io(error) => write!(f, "cannot access the persisted value `{}`: {}", self.key, error),
+            PersistErrorKind::Serialize(error) => write!(f, "cannot serialize the persisted value `{}`: {}", self.key, error),
+            PersistErrorKind::Deserialize(error) => write!(f, "cannot restore the persisted value `{}`: {}", self.key, error),
+        }
+    }
+}
+
+impl Error for PersistError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            PersistErrorKind::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+type Handler = Rc<dyn Fn(&PersistError)>;
+
+thread_local! {
+    static STORAGE: RefCell<Option<Rc<dyn StorageBackend>>> = const { RefCell::new(None) };
+    static HANDLER: RefCell<Option<Handler>> = const { RefCell::new(None) };
+}
+
+/// Sets where the values persisted on this thread are stored.
+///
+/// Set it before the first component is built, the values already restored are not read again.
+pub fn set_storage_backend(backend: impl StorageBackend + 'static) {
+    STORAGE.with(|storage| *storage.borrow_mut() = Some(Rc::new(backend)));
+}
+
+/// The storage of this thread, by default JSON files in the directory named after the executable
+/// in the configuration directory of the user.
+pub fn storage_backend() -> Rc<dyn StorageBackend> {
+    STORAGE.with(|storage| storage.borrow_mut().get_or_insert_with(|| {
+        let app = std::env::current_exe().ok()
+            .and_then(|exe| exe.file_stem().map(|name| name.to_string_lossy().into_owned()))
+            .unwrap_or_else(|| "regui".to_string());
+        Rc::new(FileStorage::in_config_dir(&app, Format::Json))
+    }).clone())
+}
+
+/// Sets the function called when a value cannot be saved or restored on this thread.
+///
+/// Without a handler, the errors are ignored: the application keeps running with the initial values.
+pub fn set_persist_error_handler(handler: impl Fn(&PersistError) + 'static) {
+    HANDLER.with(|current| *current.borrow_mut() = Some(Rc::new(handler)));
+}
+
+fn report(key: &str, kind: PersistErrorKind) {
+    let error = PersistError {
+        key: key.to_string(),
+        kind,
+    };
+    let handler = HANDLER.with(|handler| handler.borrow().clone());
+    if let Some(handler) = handler {
+        handler(&error);
+    }
+}
+
+/// Saves the value of a key when it changes.
+pub(crate) struct Saver {
+    key: String,
+    /// The text last loaded or saved.
+    saved: RefCell<Option<String>>,
+}
+
+impl Saver {
+    fn new(key: String) -> Self {
+        Self {
+            key,
+            saved: RefCell::new(None),
+        }
+    }
+
+    /// The stored value, `None` if there is none or if it cannot be restored.
+    fn load<T>(&self, deserialize: impl FnOnce(Format, &str) -> Result<T, String>) -> Option<T> {
+        let storage = storage_backend();
+        let text = match storage.load(&self.key) {
+            Ok(text) => text?,
+            Err(error) => {
+                report(&self.key, PersistErrorKind::Io(error));
+                return None;
+            }
+        };
+        match deserialize(storage.format(), &text) {
+            Ok(value) => {
+                *self.saved.borrow_mut() = Some(text);
+                Some(value)
+            }
+            Err(error) => {
+                report(&self.key, PersistErrorKind::Deserialize(error));
+                None
+            }
+        }
+    }
+
+    fn save(&self, serialize: impl FnOnce(Format) -> Result<String, String>) {
+        let storage = storage_backend();
+        let text = match serialize(storage.format()) {
+            Ok(text) => text,
+            Err(error) => return report(&self.key, PersistErrorKind::Serialize(error)),
+        };
+        if self.saved.borrow().as_ref() == Some(&text) {
+            return;
+        }
+        match storage.save(&self.key, &text) {
+            Ok(()) => *self.saved.borrow_mut() = Some(text),
+            Err(error) => report(&self.key, PersistErrorKind::Io(error)),
+        }
+    }
+}
+
+type SaveFn<C> = Box<dyn Fn(&C, Format) -> Result<String, String>>;
+type RestoreFn<C> = Box<dyn Fn(&mut C, Format, &str) -> Result<(), String>>;
+
+/// How a component is persisted, see [`Component::persistence`](crate::component::Component::persistence).
+///
+/// # Example
+/// ```ignore
+/// impl Component for Editor {
+///     // ...
+///     fn persistence(props: &EditorProps) -> Option<Persistence<Self>> {
+///         Some(Persistence::new(
+///             format!("editor-{}", props.document_id),
+///             |editor: &Editor| editor.draft.clone(),
+///             |editor, draft| editor.draft = draft,
+///         ))
+///     }
+/// }
+/// ```
+pub struct Persistence<C> {
+    saver: Saver,
+    save: SaveFn<C>,
+    restore: RestoreFn<C>,
+}
+
+impl<C: 'static> Persistence<C> {
+    /// Persists the value returned by `save` under `key`, `restore` applies it to a component just built.
+    pub fn new<P: Serialize + DeserializeOwned>(
+        key: impl Into<String>,
+        save: impl Fn(&C) -> P + 'static,
+        restore: impl Fn(&mut C, P) + 'static,
+    ) -> Self {
+        Self {
+            saver: Saver::new(key.into()),
+            save: Box::new(move |component, format| format.serialize(&save(component))),
+            restore: Box::new(move |component, format, text| {
+                restore(component, format.deserialize(text)?);
+                Ok(())
+            }),
+        }
+    }
+
+    pub(crate) fn restore(&self, component: &mut C) {
+        self.saver.load(|format, text| (self.restore)(component, format, text));
+    }
+
+    pub(crate) fn save(&self, component: &C) {
+        self.saver.save(|format| (self.save)(component, format));
+    }
+}
+
+/// Restores the value of [`Cx::use_persistent_state`](crate::function_component::Cx::use_persistent_state)
+/// on the first view, then gives the saver of the state.
+pub(crate) struct UsePersistentState<V> {
+    pub(crate) key: String,
+    pub(crate) value: PhantomData<V>,
+}
+
+impl<V: DeserializeOwned + 'static> Hook for UsePersistentState<V> {
+    /// The restored value is taken by the first view.
+    type Slot = (Rc<Saver>, Option<V>);
+    type Output = (Rc<Saver>, Option<V>);
+    fn init(&mut self, _cx: &mut HookCx) -> Self::Slot {
+        let saver = Saver::new(std::mem::take(&mut self.key));
+        let restored = saver.load(|format, text| format.deserialize(text));
+        (Rc::new(saver), restored)
+    }
+    fn call(self, (saver, restored): &mut Self::Slot, _cx: &mut HookCx) -> Self::Output {
+        (saver.clone(), restored.take())
+    }
+}
+
+/// Saves the value of `handle` if it has changed.
+pub(crate) fn save_state<V: Serialize>(saver: &Saver, handle: &UseStateHandle<V>) {
+    saver.save(|format| handle.on_value(|value| format.serialize(value)));
+}
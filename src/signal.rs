@@ -0,0 +1,486 @@
+//! Fine-grained reactivity: values that know who reads them.
+//!
+//! A [`Signal`] holds a value. Reading it while a [`Memo`], an [`Effect`] or the view of a function
+//! component runs subscribes that reader, which runs again when the signal is set. The dependencies
+//! are tracked automatically on every run, so a reader only depends on what it read last time.
+//!
+//! Unlike [`LiveValue`](crate::LiveValue)s, which tell that something changed to the parent that
+//! has to render again, a signal read by a component renders only that component.
+//!
+//! # Example
+//! ```
+//! use std::{rc::Rc, cell::RefCell};
+//! use regui::signal::{Signal, Memo, Effect};
+//!
+//! let count = Signal::new(1);
+//! let double = Memo::new({
+//!     let count = count.clone();
+//!     move || count.get() * 2
+//! });
+//!
+//! let seen = Rc::new(RefCell::new(Vec::new()));
+//! let _effect = Effect::new({
+//!     let (double, seen) = (double.clone(), seen.clone());
+//!     move || seen.borrow_mut().push(double.get())
+//! });
+//!
+//! count.set(2);
+//! count.set(2);
+//! assert_eq!(*seen.borrow(), vec![2, 4]);
+//! ```
+
+use std::{cell::{Cell, RefCell}, collections::VecDeque, fmt::{self, Debug}, rc::{Rc, Weak}};
+
+use crate::{LiveLink, LiveValue, LiveValueEmitter, ListenerGuard};
+
+/// Something that can be read by an observer.
+trait Source {
+    /// Brings the value up to date, returns its version.
+    fn refresh(&self) -> u64;
+    fn subscribe(&self, observer: Weak<dyn Observer>);
+    fn unsubscribe(&self, observer: *const ());
+}
+
+/// Something that reads sources.
+trait Observer {
+    /// One of the sources may have changed.
+    fn mark(&self);
+}
+
+/// The sources read by an observer, with the versions it has read.
+type Sources = Vec<(Rc<dyn Source>, u64)>;
+
+fn address<T: ?Sized>(rc: *const T) -> *const () {
+    rc as *const ()
+}
+
+/// The observers of a source.
+#[derive(Default)]
+struct Subscribers(RefCell<Vec<Weak<dyn Observer>>>);
+
+impl Subscribers {
+    fn add(&self, observer: Weak<dyn Observer>) {
+        let mut subscribers = self.0.borrow_mut();
+        if !subscribers.iter().any(|other| address(other.as_ptr()) == address(observer.as_ptr())) {
+            subscribers.push(observer);
+        }
+    }
+
+    fn remove(&self, observer: *const ()) {
+        self.0.borrow_mut().retain(|other| address(other.as_ptr()) != observer);
+    }
+
+    fn mark(&self) {
+        let subscribers: Vec<_> = {
+            let mut subscribers = self.0.borrow_mut();
+            subscribers.retain(|subscriber| subscriber.strong_count() > 0);
+            subscribers.iter().filter_map(Weak::upgrade).collect()
+        };
+        for subscriber in subscribers {
+            subscriber.mark();
+        }
+    }
+}
+
+thread_local! {
+    /// The sources read by the observers currently running, the innermost last.
+    static TRACKING: RefCell<Vec<(Weak<dyn Observer>, Sources)>> = const { RefCell::new(Vec::new()) };
+    /// The effects and the views to check once the signals have been set.
+    static PENDING: RefCell<VecDeque<Weak<Reaction>>> = const { RefCell::new(VecDeque::new()) };
+    static FLUSHING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Pops the running observer when dropped, even if it panics.
+struct Pop;
+
+impl Drop for Pop {
+    fn drop(&mut self) {
+        TRACKING.with(|tracking| tracking.borrow_mut().pop());
+    }
+}
+
+/// Runs `f` as `observer`, returns the sources it has read.
+///
+/// The observer is unsubscribed from `previous` and subscribed to the new sources.
+fn track<R>(observer: Weak<dyn Observer>, previous: Sources, f: impl FnOnce() -> R) -> (R, Sources) {
+    let address = address(observer.as_ptr());
+    for (source, _) in previous {
+        source.unsubscribe(address);
+    }
+    TRACKING.with(|tracking| tracking.borrow_mut().push((observer, Vec::new())));
+    let pop = Pop;
+    let result = f();
+    let sources = TRACKING.with(|tracking| std::mem::take(&mut tracking.borrow_mut().last_mut().expect("pushed above").1));
+    drop(pop);
+    (result, sources)
+}
+
+/// Records that the running observer, if any, has read `source`.
+fn read(source: Rc<dyn Source>, version: u64) {
+    TRACKING.with(|tracking| {
+        let mut tracking = tracking.borrow_mut();
+        let Some((observer, sources)) = tracking.last_mut() else {
+            return;
+        };
+        // untracked
+        if observer.strong_count() == 0 {
+            return;
+        }
+        if sources.iter().any(|(other, _)| address(Rc::as_ptr(other)) == address(Rc::as_ptr(&source))) {
+            return;
+        }
+        source.subscribe(observer.clone());
+        sources.push((source, version));
+    });
+}
+
+/// Whether any of `sources` has changed since it has been read.
+fn changed(sources: &Sources) -> bool {
+    sources.iter().any(|(source, version)| source.refresh() != *version)
+}
+
+/// Runs `f` without subscribing the running observer to what it reads.
+pub fn untrack<R>(f: impl FnOnce() -> R) -> R {
+    TRACKING.with(|tracking| tracking.borrow_mut().push((Weak::<Reaction>::new(), Vec::new())));
+    let _pop = Pop;
+    f()
+}
+
+/// Checks the scheduled reactions, unless they are already being checked.
+///
+/// If a reaction panics, the reactions scheduled after it are still checked by the next flush.
+fn flush() {
+    struct Done;
+    impl Drop for Done {
+        fn drop(&mut self) {
+            FLUSHING.with(|flushing| flushing.set(false));
+        }
+    }
+
+    if FLUSHING.with(|flushing| flushing.replace(true)) {
+        return;
+    }
+    let _done = Done;
+    while let Some(reaction) = PENDING.with(|pending| pending.borrow_mut().pop_front()) {
+        if let Some(reaction) = reaction.upgrade() {
+            reaction.check();
+        }
+    }
+}
+
+struct SignalInner<T> {
+    value: RefCell<T>,
+    version: Cell<u64>,
+    subscribers: Subscribers,
+    /// Told about the changes, see [`Signal::live_value`].
+    live_link: RefCell<Option<LiveLink>>,
+    /// The live value the signal follows, see [`Signal::from_emitter`].
+    _live_subscription: RefCell<Option<ListenerGuard>>,
+}
+
+impl<T> Source for SignalInner<T> {
+    fn refresh(&self) -> u64 {
+        self.version.get()
+    }
+    fn subscribe(&self, observer: Weak<dyn Observer>) {
+        self.subscribers.add(observer);
+    }
+    fn unsubscribe(&self, observer: *const ()) {
+        self.subscribers.remove(observer);
+    }
+}
+
+/// A value whose readers are updated when it is set, see the [module documentation](self).
+///
+/// Clones refer to the same value, and compare equal.
+pub struct Signal<T> {
+    inner: Rc<SignalInner<T>>,
+}
+
+impl<T: 'static> Signal<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            inner: Rc::new(SignalInner {
+                value: RefCell::new(value),
+                version: Cell::new(0),
+                subscribers: Subscribers::default(),
+                live_link: RefCell::new(None),
+                _live_subscription: RefCell::new(None),
+            }),
+        }
+    }
+
+    /// A signal following a live value: `read` is called again every time `emitter` tells about an update.
+    pub fn from_emitter(emitter: &LiveValueEmitter, read: impl Fn() -> T + 'static) -> Self {
+        let signal = Self::new(read());
+        let weak = Rc::downgrade(&signal.inner);
+        let subscription = emitter.listen(move || {
+            if let Some(inner) = weak.upgrade() {
+                Signal { inner }.set(read());
+            }
+        });
+        *signal.inner._live_subscription.borrow_mut() = Some(subscription);
+        signal
+    }
+
+    /// Reads the value with `f`, subscribing the running observer.
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        read(self.inner.clone(), self.inner.version.get());
+        f(&self.inner.value.borrow())
+    }
+
+    /// Reads the value without subscribing the running observer.
+    pub fn with_untracked<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.inner.value.borrow())
+    }
+
+    /// Sets the value, the readers run again.
+    pub fn set(&self, value: T) {
+        self.update(|current| *current = value);
+    }
+
+    /// Updates the value in place, the readers run again.
+    pub fn update(&self, f: impl FnOnce(&mut T)) {
+        f(&mut self.inner.value.borrow_mut());
+        self.inner.version.set(self.inner.version.get() + 1);
+        self.inner.subscribers.mark();
+        let live_link = self.inner.live_link.borrow().clone();
+        if let Some(live_link) = live_link {
+            live_link.tell_update();
+        }
+        flush();
+    }
+}
+
+impl<T: Clone + 'static> Signal<T> {
+    /// The value, subscribing the running observer.
+    pub fn get(&self) -> T {
+        self.with(T::clone)
+    }
+
+    /// The value, without subscribing the running observer.
+    pub fn get_untracked(&self) -> T {
+        self.with_untracked(T::clone)
+    }
+
+    /// The current value as a live value, whose emitter tells when the signal is set.
+    pub fn live_value(&self) -> LiveValue<T> {
+        let live_link = self.inner.live_link.borrow_mut().get_or_insert_with(LiveLink::new).clone();
+        live_link.make_live_value(self.get_untracked())
+    }
+}
+
+impl<T: Clone + 'static> From<&Signal<T>> for LiveValue<T> {
+    fn from(signal: &Signal<T>) -> Self {
+        signal.live_value()
+    }
+}
+
+impl<T> Clone for Signal<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> PartialEq for Signal<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl<T: Debug> Debug for Signal<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Signal").field(&*self.inner.value.borrow()).finish()
+    }
+}
+
+struct MemoInner<T> {
+    compute: Box<dyn Fn() -> T>,
+    /// `None` until the first read.
+    value: RefCell<Option<T>>,
+    version: Cell<u64>,
+    /// Whether a source may have changed since the last computation.
+    stale: Cell<bool>,
+    sources: RefCell<Sources>,
+    subscribers: Subscribers,
+    this: Weak<MemoInner<T>>,
+}
+
+impl<T: PartialEq + 'static> Source for MemoInner<T> {
+    fn refresh(&self) -> u64 {
+        if !self.stale.replace(false) {
+            return self.version.get();
+        }
+        let computed = self.value.borrow().is_some();
+        if computed && !changed(&self.sources.borrow()) {
+            return self.version.get();
+        }
+
+        let previous = std::mem::take(&mut *self.sources.borrow_mut());
+        let (value, sources) = track(self.this.clone(), previous, &self.compute);
+        *self.sources.borrow_mut() = sources;
+        let mut current = self.value.borrow_mut();
+        if current.as_ref() != Some(&value) {
+            *current = Some(value);
+            self.version.set(self.version.get() + 1);
+        }
+        self.version.get()
+    }
+    fn subscribe(&self, observer: Weak<dyn Observer>) {
+        self.subscribers.add(observer);
+    }
+    fn unsubscribe(&self, observer: *const ()) {
+        self.subscribers.remove(observer);
+    }
+}
+
+impl<T> Observer for MemoInner<T> {
+    fn mark(&self) {
+        if !self.stale.replace(true) {
+            self.subscribers.mark();
+        }
+    }
+}
+
+/// A value computed from signals and other memos, see the [module documentation](self).
+///
+/// The value is computed when it is read, and again only when it is read after a change of what
+/// the computation has read. The readers of a memo run again only if its value has changed.
+///
+/// Clones refer to the same memo, and compare equal.
+pub struct Memo<T> {
+    inner: Rc<MemoInner<T>>,
+}
+
+impl<T: PartialEq + 'static> Memo<T> {
+    pub fn new(compute: impl Fn() -> T + 'static) -> Self {
+        Self {
+            inner: Rc::new_cyclic(|this| MemoInner {
+                compute: Box::new(compute),
+                value: RefCell::new(None),
+                version: Cell::new(0),
+                stale: Cell::new(true),
+                sources: RefCell::new(Vec::new()),
+                subscribers: Subscribers::default(),
+                this: this.clone(),
+            }),
+        }
+    }
+
+    /// Reads the value with `f`, subscribing the running observer.
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        let version = self.inner.refresh();
+        read(self.inner.clone(), version);
+        f(self.inner.value.borrow().as_ref().expect("refreshed above"))
+    }
+}
+
+impl<T: PartialEq + Clone + 'static> Memo<T> {
+    /// The value, subscribing the running observer.
+    pub fn get(&self) -> T {
+        self.with(T::clone)
+    }
+}
+
+impl<T> Clone for Memo<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> PartialEq for Memo<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+/// Runs an action when the sources read by the last run change: an [`Effect`] or the view of a component.
+struct Reaction {
+    action: RefCell<Box<dyn FnMut()>>,
+    /// Whether the action itself is tracked, otherwise the sources are given by [`ViewTracker::track`].
+    tracked: bool,
+    sources: RefCell<Sources>,
+    scheduled: Cell<bool>,
+    this: Weak<Reaction>,
+}
+
+impl Reaction {
+    fn new(tracked: bool, action: impl FnMut() + 'static) -> Rc<Self> {
+        Rc::new_cyclic(|this| Self {
+            action: RefCell::new(Box::new(action)),
+            tracked,
+            sources: RefCell::new(Vec::new()),
+            scheduled: Cell::new(false),
+            this: this.clone(),
+        })
+    }
+
+    fn run(&self) {
+        if self.tracked {
+            let previous = std::mem::take(&mut *self.sources.borrow_mut());
+            let ((), sources) = track(self.this.clone(), previous, || (self.action.borrow_mut())());
+            *self.sources.borrow_mut() = sources;
+        } else {
+            (self.action.borrow_mut())();
+        }
+    }
+
+    /// Runs the action if a source has really changed.
+    fn check(&self) {
+        self.scheduled.set(false);
+        if changed(&self.sources.borrow()) {
+            self.run();
+        }
+    }
+}
+
+impl Observer for Reaction {
+    fn mark(&self) {
+        if !self.scheduled.replace(true) {
+            PENDING.with(|pending| pending.borrow_mut().push_back(self.this.clone()));
+        }
+    }
+}
+
+/// Runs a function now and every time the signals and the memos it has read change, until dropped.
+///
+/// See the [module documentation](self).
+#[must_use = "dropping the effect stops it"]
+pub struct Effect {
+    _reaction: Rc<Reaction>,
+}
+
+impl Effect {
+    pub fn new(effect: impl FnMut() + 'static) -> Self {
+        let reaction = Reaction::new(true, effect);
+        reaction.run();
+        Self {
+            _reaction: reaction,
+        }
+    }
+}
+
+/// Tracks the signals read by the view of a component, to render it again when they change.
+pub(crate) struct ViewTracker {
+    reaction: Rc<Reaction>,
+}
+
+impl ViewTracker {
+    pub(crate) fn new(rerender: impl Fn() + 'static) -> Self {
+        Self {
+            reaction: Reaction::new(false, rerender),
+        }
+    }
+
+    pub(crate) fn track<R>(&self, view: impl FnOnce() -> R) -> R {
+        let reaction = &self.reaction;
+        let previous = std::mem::take(&mut *reaction.sources.borrow_mut());
+        let (result, sources) = track(reaction.this.clone(), previous, view);
+        *reaction.sources.borrow_mut() = sources;
+        result
+    }
+}
@@ -1,5 +1,5 @@
 
-use std::{rc::Rc, cell::RefCell};
+use std::{rc::{Rc, Weak}, cell::RefCell};
 
 /// A live value represents a value that can be updated at any time.
 ///
@@ -18,7 +18,7 @@ use std::{rc::Rc, cell::RefCell};
 /// let changed: Rc<RefCell<bool>> = Rc::new(RefCell::new(false));
 /// 
 /// let (value, emitter) = link.make_live_value(42).into_tuple();
-/// emitter.listen({
+/// let subscription = emitter.listen({
 ///     let changed = changed.clone();
 ///     move || { changed.replace(true); }
 /// });
@@ -28,6 +28,12 @@ use std::{rc::Rc, cell::RefCell};
 ///
 /// link.tell_update();
 /// assert!(*changed.borrow());
+///
+/// // dropping the subscription stops listening
+/// changed.replace(false);
+/// drop(subscription);
+/// link.tell_update();
+/// assert!(!*changed.borrow());
 /// ```
 pub struct LiveValue<T> {
     pub value: T,
@@ -44,7 +50,7 @@ impl<T> LiveValue<T> {
 }
 
 /// A live link is used to create live values.
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct LiveLink {
     inner: Rc<RefCell<LiveInner>>,
 }
@@ -56,8 +62,46 @@ pub struct LiveValueEmitter {
 
 impl LiveValueEmitter {
     /// Listen for updates.
-    pub fn listen(&self, listener: impl FnMut() + 'static) { // TODO maybe mut?
-        self.inner.borrow_mut().listener = Some(RefCell::new(Rc::new(RefCell::new(listener))));
+    ///
+    /// Any number of listeners can listen to the same live link, each one listens
+    /// until the returned [`ListenerGuard`] is dropped.
+    ///
+    /// # Example
+    /// ```
+    /// use std::{rc::Rc, cell::{Cell, RefCell}};
+    /// use regui::*;
+    ///
+    /// let link = LiveLink::new();
+    /// let emitter = link.make_live_value(()).emitter;
+    /// let calls = Rc::new(Cell::new(0));
+    ///
+    /// // the first listener unsubscribes the second one
+    /// let second = Rc::new(RefCell::new(None::<ListenerGuard>));
+    /// let _first = emitter.listen({
+    ///     let (calls, second) = (calls.clone(), second.clone());
+    ///     move || {
+    ///         calls.set(calls.get() + 1);
+    ///         second.borrow_mut().take();
+    ///     }
+    /// });
+    /// *second.borrow_mut() = Some(emitter.listen({
+    ///     let calls = calls.clone();
+    ///     move || calls.set(calls.get() + 10)
+    /// }));
+    ///
+    /// link.tell_update();
+    /// assert_eq!(calls.get(), 1);
+    /// ```
+    #[must_use = "dropping the subscription stops listening"]
+    pub fn listen(&self, listener: impl FnMut() + 'static) -> ListenerGuard {
+        let mut inner = self.inner.borrow_mut();
+        let id = inner.next_id;
+        inner.next_id += 1;
+        inner.listeners.push((id, Rc::new(RefCell::new(listener))));
+        ListenerGuard {
+            inner: Rc::downgrade(&self.inner),
+            id,
+        }
     }
 }
 
@@ -66,19 +110,24 @@ impl LiveLink {
     pub fn new() -> Self {
         Self {
             inner: Rc::new(RefCell::new(LiveInner {
-                listener: None,
+                listeners: Vec::new(),
+                next_id: 0,
             })),
         }
     }
 
-    /// Tell the listener that the value has been updated.
+    /// Tell the listeners that the value has been updated.
+    ///
+    /// The listeners are called in subscription order. A listener unsubscribed by
+    /// a previous one is not called, listeners subscribed meanwhile are called on the next update.
     pub fn tell_update(&self) {
-        let listener = self.inner.borrow()
-            .listener
-            .as_ref().map(|listener| listener.borrow().clone());
+        let listeners = self.inner.borrow().listeners.clone();
 
-        if let Some(listener) = listener {
-            (listener.borrow_mut())();
+        for (id, listener) in listeners {
+            let subscribed = self.inner.borrow().listeners.iter().any(|(other, _)| *other == id);
+            if subscribed {
+                (listener.borrow_mut())();
+            }
         }
     }
 
@@ -93,6 +142,41 @@ impl LiveLink {
     }
 }
 
+/// Listens to a [`LiveValueEmitter`] until dropped.
+///
+/// This is the subscription guard returned by [`LiveValueEmitter::listen`]. It is not named
+/// `Subscription` since that name is taken by [`subscription::Subscription`](crate::subscription::Subscription),
+/// the sources of messages of the components.
+pub struct ListenerGuard {
+    inner: Weak<RefCell<LiveInner>>,
+    id: usize,
+}
+
+impl ListenerGuard {
+    /// Keeps listening for as long as the live link exists.
+    pub fn detach(self) {
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for ListenerGuard {
+    fn drop(&mut self) {
+        if let Some(inner) = self.inner.upgrade() {
+            let mut inner = inner.borrow_mut();
+            let removed = inner.listeners.iter()
+                .position(|(id, _)| *id == self.id)
+                .map(|index| inner.listeners.remove(index));
+            // the listener could own something listening to the same link
+            drop(inner);
+            drop(removed);
+        }
+    }
+}
+
+type Listener = Rc<RefCell<dyn FnMut()>>;
+
+#[derive(Default)]
 struct LiveInner {
-    listener: Option<RefCell<Rc<RefCell<dyn FnMut()>>>>, // TODO maybe remove the first cell and use Cell instead of RefCell for the second one
+    listeners: Vec<(usize, Listener)>,
+    next_id: usize,
 }
\ No newline at end of file
@@ -0,0 +1,221 @@
+//! A state shared by the whole application, or by a subtree.
+//!
+//! A [`Store`] holds a state implementing [`Reducible`]: the state is only changed by the actions
+//! [dispatched](Store::dispatch) to the store. Provide the store as a context, then the
+//! components select what they need with [`Cx::use_store`](crate::function_component::Cx::use_store),
+//! and are rendered again only when their selection changes.
+//!
+//! Like the updates of a [`StateManager`](crate::component::StateManager), the actions dispatched
+//! while an action is running are queued, and run in order once it has returned. The listeners
+//! are told about the changes once the queue is empty.
+//!
+//! # Example
+//! ```
+//! use std::{rc::Rc, cell::RefCell};
+//! use regui::store::{Reducible, Store};
+//!
+//! #[derive(Default)]
+//! struct Todos {
+//!     items: Vec<String>,
+//! }
+//!
+//! enum TodoAction {
+//!     Add(String),
+//!     Clear,
+//! }
+//!
+//! impl Reducible for Todos {
+//!     type Action = TodoAction;
+//!     fn reduce(&mut self, action: TodoAction) {
+//!         match action {
+//!             TodoAction::Add(item) => self.items.push(item),
+//!             TodoAction::Clear => self.items.clear(),
+//!         }
+//!     }
+//! }
+//!
+//! let log = Rc::new(RefCell::new(Vec::new()));
+//! let store = Store::new(Todos::default())
+//!     .with_middleware({
+//!         let log = log.clone();
+//!         move |action: TodoAction, next: regui::store::Next<Todos>| {
+//!             let before = next.state().items.len();
+//!             next.run(action);
+//!             log.borrow_mut().push(before);
+//!         }
+//!     });
+//!
+//! store.dispatch(TodoAction::Add("milk".into()));
+//! store.dispatch(TodoAction::Add("eggs".into()));
+//! assert_eq!(store.with(|todos| todos.items.len()), 2);
+//! assert_eq!(*log.borrow(), vec![0, 1]);
+//! ```
+
+use std::{cell::{Cell, RefCell}, collections::VecDeque, rc::Rc};
+
+use crate::{LiveLink, ListenerGuard, component::batch};
+
+/// A state changed by actions, see [`Store`].
+pub trait Reducible: 'static {
+    type Action: 'static;
+
+    /// Applies `action` to the state.
+    fn reduce(&mut self, action: Self::Action);
+}
+
+/// Wraps the dispatch of the actions of a [`Store`], e.g. to log them or to record the states to undo them.
+///
+/// A middleware calls [`Next::run`] to pass the action to the next middleware, and eventually to
+/// [`Reducible::reduce`], or drops `next` to discard the action. Closures taking the action and
+/// [`Next`] are middlewares.
+pub trait Middleware<S: Reducible> {
+    fn dispatch(&mut self, action: S::Action, next: Next<S>);
+}
+
+impl<S: Reducible, F: FnMut(S::Action, Next<S>)> Middleware<S> for F {
+    fn dispatch(&mut self, action: S::Action, next: Next<S>) {
+        self(action, next)
+    }
+}
+
+/// The rest of the dispatch of an action, given to a [`Middleware`].
+pub struct Next<'a, S: Reducible> {
+    state: &'a mut S,
+    middlewares: &'a mut [Box<dyn Middleware<S>>],
+}
+
+impl<'a, S: Reducible> Next<'a, S> {
+    /// The state before the action.
+    pub fn state(&self) -> &S {
+        self.state
+    }
+
+    /// The state, to replace it without running an action, e.g. to restore a previous state.
+    pub fn state_mut(&mut self) -> &mut S {
+        self.state
+    }
+
+    /// Runs the next middlewares then the reducer with `action`.
+    pub fn run(self, action: S::Action) {
+        match self.middlewares.split_first_mut() {
+            Some((middleware, middlewares)) => middleware.dispatch(action, Next {
+                state: self.state,
+                middlewares,
+            }),
+            None => self.state.reduce(action),
+        }
+    }
+}
+
+struct StoreInner<S: Reducible> {
+    state: RefCell<S>,
+    middlewares: RefCell<Vec<Box<dyn Middleware<S>>>>,
+    /// The actions dispatched while an action is running.
+    queue: RefCell<VecDeque<S::Action>>,
+    running: Cell<bool>,
+    listeners: LiveLink,
+}
+
+/// Holds a [`Reducible`] state shared by the components, see the [module documentation](self).
+///
+/// Clones refer to the same store, and compare equal: a store can be provided as a context.
+pub struct Store<S: Reducible> {
+    inner: Rc<StoreInner<S>>,
+}
+
+impl<S: Reducible> Store<S> {
+    pub fn new(state: S) -> Self {
+        Self {
+            inner: Rc::new(StoreInner {
+                state: RefCell::new(state),
+                middlewares: RefCell::new(Vec::new()),
+                queue: RefCell::new(VecDeque::new()),
+                running: Cell::new(false),
+                listeners: LiveLink::new(),
+            }),
+        }
+    }
+
+    /// Adds a middleware, the middlewares added first see the actions first.
+    pub fn with_middleware(self, middleware: impl Middleware<S> + 'static) -> Self {
+        self.inner.middlewares.borrow_mut().push(Box::new(middleware));
+        self
+    }
+
+    /// Runs `action` through the middlewares then the reducer, and tells the listeners.
+    ///
+    /// If an action is already running, e.g. when a middleware dispatches an action, `action`
+    /// is queued and runs after it. If an action panics, the actions queued after it are discarded,
+    /// and the store accepts new actions.
+    ///
+    /// The components selecting from the store are rendered after all the listeners have been told,
+    /// so their effects and handlers can dispatch again.
+    pub fn dispatch(&self, action: S::Action) {
+        struct Running<'a, S: Reducible>(&'a StoreInner<S>);
+        impl<S: Reducible> Drop for Running<'_, S> {
+            fn drop(&mut self) {
+                self.0.queue.borrow_mut().clear();
+                self.0.running.set(false);
+            }
+        }
+
+        self.inner.queue.borrow_mut().push_back(action);
+        if self.inner.running.replace(true) {
+            return;
+        }
+
+        let running = Running(&self.inner);
+        loop {
+            // not borrowed while the action runs
+            let Some(action) = self.inner.queue.borrow_mut().pop_front() else {
+                break;
+            };
+            let mut state = self.inner.state.borrow_mut();
+            let mut middlewares = self.inner.middlewares.borrow_mut();
+            Next::<S> {
+                state: &mut *state,
+                middlewares: &mut middlewares,
+            }.run(action);
+        }
+        drop(running);
+        // the listeners read the state, the components they update are rendered once it is released
+        batch(|| self.inner.listeners.tell_update());
+    }
+
+    /// Reads the state with `f`.
+    pub fn with<R>(&self, f: impl FnOnce(&S) -> R) -> R {
+        f(&self.inner.state.try_borrow().expect("the state of a store cannot be read while an action runs, use `Next::state`"))
+    }
+
+    /// Calls `listener` with the state after the actions, until the subscription is dropped.
+    #[must_use = "dropping the subscription stops listening"]
+    pub fn listen(&self, mut listener: impl FnMut(&S) + 'static) -> ListenerGuard {
+        let inner = Rc::downgrade(&self.inner);
+        self.inner.listeners.make_live_value(()).emitter.listen(move || {
+            if let Some(inner) = inner.upgrade() {
+                listener(&inner.state.borrow());
+            }
+        })
+    }
+}
+
+impl<S: Reducible + Clone> Store<S> {
+    /// A copy of the state.
+    pub fn get(&self) -> S {
+        self.with(S::clone)
+    }
+}
+
+impl<S: Reducible> Clone for Store<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<S: Reducible> PartialEq for Store<S> {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.inner, &other.inner)
+    }
+}
@@ -0,0 +1,166 @@
+//! Listening to timers and external events while a component is alive.
+//!
+//! A component declares its subscriptions on every view, see
+//! [`Component::subscriptions`](crate::component::Component::subscriptions) and
+//! [`Cx::use_subscription`](crate::function_component::Cx::use_subscription).
+//! The subscriptions are identified by an id: a subscription is started the first time its id
+//! appears and is cancelled when its id disappears or when the component is dropped. A subscription
+//! whose parameters change, e.g. the period of an [`interval`](Subscription::interval), is restarted.
+
+use std::{collections::HashMap, hash::Hash, rc::Rc, time::Duration, future::poll_fn};
+
+use futures_core::Stream;
+
+use crate::{LiveValueEmitter, task, utils::AnyKey};
+
+type Sender<M> = Rc<dyn Fn(M)>;
+type Cancel = Box<dyn FnOnce()>;
+
+/// Something to listen to while a component is alive, producing messages of type `M`.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use regui::subscription::Subscription;
+///
+/// enum Message {
+///     Tick,
+/// }
+///
+/// fn subscriptions(running: bool) -> Vec<Subscription<Message>> {
+///     if running {
+///         vec![Subscription::interval("clock", Duration::from_secs(1), || Message::Tick)]
+///     } else {
+///         Vec::new()
+///     }
+/// }
+/// ```
+pub struct Subscription<M> {
+    id: AnyKey,
+    /// The parameters the subscription has been started with, compared when its id is found again.
+    params: Option<AnyKey>,
+    start: Box<dyn FnOnce(Sender<M>) -> Cancel>,
+}
+
+impl<M: 'static> Subscription<M> {
+    /// A custom subscription.
+    ///
+    /// `start` is called when the subscription first appears, with the function sending the messages
+    /// to the component. It returns the function stopping the subscription.
+    pub fn new<C>(id: impl Hash + Eq + 'static, start: impl FnOnce(Rc<dyn Fn(M)>) -> C + 'static) -> Self
+    where
+        C: FnOnce() + 'static,
+    {
+        Self {
+            id: AnyKey::new(id),
+            params: None,
+            start: Box::new(move |send| Box::new(start(send))),
+        }
+    }
+
+    /// Restarts the subscription when `params` differ from the ones it has been started with.
+    ///
+    /// Otherwise a running subscription is kept as long as its id is found, even if it has been
+    /// created with other values.
+    pub fn with_params(mut self, params: impl Hash + Eq + 'static) -> Self {
+        self.params = Some(AnyKey::new(params));
+        self
+    }
+
+    /// Sends `message()` every `period`, using the spawner of the kit.
+    ///
+    /// The interval is restarted when `period` changes.
+    pub fn interval(id: impl Hash + Eq + 'static, period: Duration, message: impl Fn() -> M + 'static) -> Self {
+        Self::new(id, move |send| {
+            let task = task::spawn_local(async move {
+                loop {
+                    task::sleep(period).await;
+                    send(message());
+                }
+            });
+            move || task.abort()
+        }).with_params(period)
+    }
+
+    /// Sends the items of `stream`, converted by `message`, using the spawner of the kit.
+    pub fn stream<S>(id: impl Hash + Eq + 'static, stream: S, message: impl Fn(S::Item) -> M + 'static) -> Self
+    where
+        S: Stream + 'static,
+    {
+        Self::new(id, move |send| {
+            let task = task::spawn_local(async move {
+                let mut stream = Box::pin(stream);
+                while let Some(item) = poll_fn(|cx| stream.as_mut().poll_next(cx)).await {
+                    send(message(item));
+                }
+            });
+            move || task.abort()
+        })
+    }
+
+    /// Sends `message()` every time the live value behind `emitter` changes.
+    pub fn live_value(id: impl Hash + Eq + 'static, emitter: LiveValueEmitter, message: impl Fn() -> M + 'static) -> Self {
+        Self::new(id, move |send| {
+            let subscription = emitter.listen(move || send(message()));
+            move || drop(subscription)
+        })
+    }
+
+    /// Converts the messages, e.g. to forward the subscriptions of a part of the state.
+    pub fn map<N: 'static>(self, f: impl Fn(M) -> N + 'static) -> Subscription<N> {
+        let start = self.start;
+        Subscription {
+            id: self.id,
+            params: self.params,
+            start: Box::new(move |send: Sender<N>| start(Rc::new(move |message| send(f(message))))),
+        }
+    }
+}
+
+/// The running subscriptions of a component.
+pub(crate) struct Subscriptions {
+    /// The parameters and the cancel function of the running subscriptions, by id.
+    running: HashMap<AnyKey, (Option<AnyKey>, Cancel)>,
+}
+
+impl Subscriptions {
+    pub(crate) fn new() -> Self {
+        Self {
+            running: HashMap::new(),
+        }
+    }
+
+    /// Starts the new subscriptions, restarts the ones whose parameters have changed and cancels
+    /// the ones that are not in `subscriptions` anymore.
+    ///
+    /// If two subscriptions have the same id, the first one is used.
+    pub(crate) fn update<M: 'static>(&mut self, subscriptions: impl IntoIterator<Item = Subscription<M>>, send: Sender<M>) {
+        let mut previous = std::mem::take(&mut self.running);
+        for subscription in subscriptions {
+            if self.running.contains_key(&subscription.id) {
+                continue;
+            }
+            let running = match previous.remove(&subscription.id) {
+                Some((params, cancel)) if params == subscription.params => (params, cancel),
+                restarted => {
+                    if let Some((_, cancel)) = restarted {
+                        cancel();
+                    }
+                    (subscription.params, (subscription.start)(send.clone()))
+                }
+            };
+            self.running.insert(subscription.id, running);
+        }
+        for (_, (_, cancel)) in previous {
+            cancel();
+        }
+    }
+}
+
+impl Drop for Subscriptions {
+    fn drop(&mut self) {
+        for (_, (_, cancel)) in self.running.drain() {
+            cancel();
+        }
+    }
+}
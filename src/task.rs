@@ -0,0 +1,187 @@
+//! Running futures on the UI thread.
+//!
+//! The core does not depend on an async runtime: the kits provide one through a [`Spawner`].
+
+use std::{future::Future, pin::Pin, rc::Rc, cell::{Cell, RefCell}, task::{Context, Poll, Waker}, time::Duration};
+
+/// A boxed future running on the UI thread.
+pub type LocalFuture<T = ()> = Pin<Box<dyn Future<Output = T>>>;
+
+/// The executor of the futures started by the components, provided by the kits with [`set_spawner`].
+///
+/// # Example
+/// With tokio, inside a `LocalSet`:
+/// ```ignore
+/// struct TokioSpawner;
+///
+/// impl Spawner for TokioSpawner {
+///     fn spawn_local(&self, future: LocalFuture) {
+///         tokio::task::spawn_local(future);
+///     }
+///     fn sleep(&self, duration: Duration) -> LocalFuture {
+///         Box::pin(tokio::time::sleep(duration))
+///     }
+/// }
+/// ```
+pub trait Spawner {
+    /// Runs `future` to completion on the current thread.
+    fn spawn_local(&self, future: LocalFuture);
+
+    /// A future completing after `duration`.
+    fn sleep(&self, duration: Duration) -> LocalFuture;
+}
+
+thread_local! {
+    static SPAWNER: RefCell<Option<Rc<dyn Spawner>>> = const { RefCell::new(None) };
+}
+
+/// Sets the spawner of the current thread.
+pub fn set_spawner(spawner: impl Spawner + 'static) {
+    SPAWNER.with(|current| *current.borrow_mut() = Some(Rc::new(spawner)));
+}
+
+fn spawner() -> Rc<dyn Spawner> {
+    SPAWNER.with(|spawner| spawner.borrow().clone())
+        .expect("no spawner on this thread, the kit should set one with `regui::task::set_spawner`")
+}
+
+/// Runs `future` with the spawner of the current thread.
+///
+/// # Panics
+/// Panics if no spawner has been set.
+pub fn spawn_local(future: impl Future<Output = ()> + 'static) -> TaskHandle {
+    let state = Rc::new(TaskState {
+        aborted: Cell::new(false),
+        finished: Cell::new(false),
+        waker: RefCell::new(None),
+    });
+    spawner().spawn_local(Box::pin(Abortable {
+        future: Box::pin(future),
+        state: state.clone(),
+    }));
+    TaskHandle { state }
+}
+
+/// A future completing after `duration`, created by the spawner of the current thread.
+///
+/// # Panics
+/// Panics if no spawner has been set.
+pub fn sleep(duration: Duration) -> LocalFuture {
+    spawner().sleep(duration)
+}
+
+struct TaskState {
+    aborted: Cell<bool>,
+    finished: Cell<bool>,
+    /// The waker of the last poll, to have the task polled again once aborted.
+    waker: RefCell<Option<Waker>>,
+}
+
+/// A handle to a task started with [`spawn_local`].
+///
+/// Dropping the handle does not abort the task.
+pub struct TaskHandle {
+    state: Rc<TaskState>,
+}
+
+impl TaskHandle {
+    /// Stops the task, its future is not polled anymore and it is dropped by the executor.
+    pub fn abort(&self) {
+        self.state.aborted.set(true);
+        if let Some(waker) = self.state.waker.borrow_mut().take() {
+            waker.wake();
+        }
+    }
+
+    /// Whether the task has completed or has been aborted.
+    pub fn is_finished(&self) -> bool {
+        self.state.finished.get() || self.state.aborted.get()
+    }
+}
+
+struct Abortable {
+    future: LocalFuture,
+    state: Rc<TaskState>,
+}
+
+impl Future for Abortable {
+    type Output = ();
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.state.aborted.get() {
+            return Poll::Ready(());
+        }
+        match self.future.as_mut().poll(cx) {
+            Poll::Ready(()) => {
+                self.state.finished.set(true);
+                Poll::Ready(())
+            }
+            Poll::Pending => {
+                *self.state.waker.borrow_mut() = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// The state of an asynchronous value, e.g. the result of [`Cx::use_future`](crate::function_component::Cx::use_future).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Resource<T, E = std::convert::Infallible> {
+    /// The future is running.
+    #[default]
+    Pending,
+    /// The future has completed.
+    Ready(T),
+    /// The future has failed.
+    Error(E),
+}
+
+impl<T, E> Resource<T, E> {
+    pub fn is_pending(&self) -> bool {
+        matches!(self, Self::Pending)
+    }
+
+    pub fn is_ready(&self) -> bool {
+        matches!(self, Self::Ready(_))
+    }
+
+    /// The value, if the future has completed.
+    pub fn value(&self) -> Option<&T> {
+        match self {
+            Self::Ready(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The error, if the future has failed.
+    pub fn error(&self) -> Option<&E> {
+        match self {
+            Self::Error(error) => Some(error),
+            _ => None,
+        }
+    }
+
+    pub fn as_ref(&self) -> Resource<&T, &E> {
+        match self {
+            Self::Pending => Resource::Pending,
+            Self::Ready(value) => Resource::Ready(value),
+            Self::Error(error) => Resource::Error(error),
+        }
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Resource<U, E> {
+        match self {
+            Self::Pending => Resource::Pending,
+            Self::Ready(value) => Resource::Ready(f(value)),
+            Self::Error(error) => Resource::Error(error),
+        }
+    }
+}
+
+impl<T, E> From<Result<T, E>> for Resource<T, E> {
+    fn from(result: Result<T, E>) -> Self {
+        match result {
+            Ok(value) => Self::Ready(value),
+            Err(error) => Self::Error(error),
+        }
+    }
+}
@@ -1,3 +1,2 @@
-
-
-mod pre_eq_rc; pub use pre_eq_rc::PtrEqRc;
\ No newline at end of file
+mod pre_eq_rc; pub use pre_eq_rc::PtrEqRc;
+mod any_key; pub use any_key::AnyKey;
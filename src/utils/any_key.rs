@@ -0,0 +1,51 @@
+use std::{any::Any, hash::{Hash, Hasher}, fmt::Debug};
+
+/// A type-erased `Hash + Eq` key.
+///
+/// Two keys are equal only if they wrap values of the same type that compare equal.
+pub struct AnyKey(Box<dyn DynKey>);
+
+impl AnyKey {
+    pub fn new<K: Hash + Eq + 'static>(key: K) -> Self {
+        Self(Box::new(key))
+    }
+}
+
+impl PartialEq for AnyKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.dyn_eq(other.0.as_any())
+    }
+}
+
+impl Eq for AnyKey {}
+
+impl Hash for AnyKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Any::type_id(self.0.as_any()).hash(state);
+        self.0.dyn_hash(state);
+    }
+}
+
+impl Debug for AnyKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("AnyKey").finish_non_exhaustive()
+    }
+}
+
+trait DynKey {
+    fn as_any(&self) -> &dyn Any;
+    fn dyn_eq(&self, other: &dyn Any) -> bool;
+    fn dyn_hash(&self, state: &mut dyn Hasher);
+}
+
+impl<K: Hash + Eq + 'static> DynKey for K {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn dyn_eq(&self, other: &dyn Any) -> bool {
+        other.downcast_ref::<K>().is_some_and(|other| self == other)
+    }
+    fn dyn_hash(&self, mut state: &mut dyn Hasher) {
+        self.hash(&mut state);
+    }
+}
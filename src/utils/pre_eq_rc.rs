@@ -1,43 +1,49 @@
 use std::{rc::Rc, ops::Deref};
 
 #[derive(Debug)]
-pub struct PtrEqRc<T>(Rc<T>);
+pub struct PtrEqRc<T: ?Sized>(Rc<T>);
 
 impl<T> PtrEqRc<T> {
     pub fn new(value: T) -> Self {
         Self(Rc::new(value))
     }
+}
+
+impl<T: ?Sized> PtrEqRc<T> {
     pub fn rc_ref(&self) -> &Rc<T> {
         &self.0
     }
+    pub fn into_rc(self) -> Rc<T> {
+        self.0
+    }
 }
 
-impl<T> From<PtrEqRc<T>> for Rc<T> {
+impl<T: ?Sized> From<PtrEqRc<T>> for Rc<T> {
     fn from(ptr_eq_rc: PtrEqRc<T>) -> Self {
         ptr_eq_rc.0
     }
 }
 
-impl<T> From<Rc<T>> for PtrEqRc<T> {
+impl<T: ?Sized> From<Rc<T>> for PtrEqRc<T> {
     fn from(rc: Rc<T>) -> Self {
         Self(rc)
     }
 }
 
-impl<T> Deref for PtrEqRc<T> {
+impl<T: ?Sized> Deref for PtrEqRc<T> {
     type Target = T;
     fn deref(&self) -> &Self::Target {
         &self.0
     }
 }
 
-impl<T> PartialEq for PtrEqRc<T> {
+impl<T: ?Sized> PartialEq for PtrEqRc<T> {
     fn eq(&self, other: &Self) -> bool {
         Rc::ptr_eq(&self.0, &other.0)
     }
 }
 
-impl<T> Clone for PtrEqRc<T> {
+impl<T: ?Sized> Clone for PtrEqRc<T> {
     fn clone(&self) -> Self {
         Self(self.0.clone())
     }
@@ -0,0 +1,181 @@
+//! Counts the `view` calls of nested components, each pass should render a component at most once.
+
+use std::{rc::Rc, cell::{Cell, RefCell}};
+
+use regui::{component::{Command, Component, FunctionsCache, LiveStateComponent, ShouldRender, StateLink}, StateFunction};
+
+type Log = Rc<RefCell<Vec<&'static str>>>;
+type Slot<T> = Rc<RefCell<Option<StateLink<T>>>>;
+
+struct ChildProps {
+    log: Log,
+    link: Slot<Child>,
+    value: i32,
+}
+
+struct Child {
+    log: Log,
+    link: Slot<Child>,
+    value: i32,
+    /// The state owned by the child.
+    own: i32,
+}
+
+impl Component for Child {
+    type Props = ChildProps;
+    type Out = i32;
+    type Message = i32;
+
+    fn build(props: ChildProps, _link: StateLink<Self>) -> Self {
+        Self { log: props.log, link: props.link, value: props.value, own: 0 }
+    }
+
+    fn update(&mut self, props: ChildProps, _link: StateLink<Self>) -> ShouldRender {
+        let changed = props.value != self.value;
+        self.value = props.value;
+        changed
+    }
+
+    fn on_message(&mut self, own: i32, _link: StateLink<Self>) -> Command<Self::Message> {
+        self.own = own;
+        Command::none()
+    }
+
+    fn view(&self, link: StateLink<Self>, _cache: &FunctionsCache) -> i32 {
+        self.log.borrow_mut().push("child");
+        *self.link.borrow_mut() = Some(link);
+        self.value + self.own
+    }
+}
+
+enum ParentMessage {
+    SetChildValue(i32),
+    SetUnrelated(i32),
+}
+
+struct Parent {
+    log: Log,
+    link: Slot<Parent>,
+    child_link: Slot<Child>,
+    child_value: i32,
+    unrelated: i32,
+}
+
+impl Component for Parent {
+    type Props = (Log, Slot<Parent>, Slot<Child>);
+    type Out = (i32, i32);
+    type Message = ParentMessage;
+
+    fn build((log, link, child_link): Self::Props, _link: StateLink<Self>) -> Self {
+        Self { log, link, child_link, child_value: 0, unrelated: 0 }
+    }
+
+    fn on_message(&mut self, message: ParentMessage, _link: StateLink<Self>) -> Command<Self::Message> {
+        match message {
+            ParentMessage::SetChildValue(value) => self.child_value = value,
+            ParentMessage::SetUnrelated(value) => self.unrelated = value,
+        }
+        Command::none()
+    }
+
+    fn view(&self, link: StateLink<Self>, cache: &FunctionsCache) -> (i32, i32) {
+        self.log.borrow_mut().push("parent");
+        *self.link.borrow_mut() = Some(link);
+        let child = cache.eval_live::<LiveStateComponent<Child>, i32>(ChildProps {
+            log: self.log.clone(),
+            link: self.child_link.clone(),
+            value: self.child_value,
+        });
+        (self.unrelated, child)
+    }
+}
+
+struct App {
+    log: Log,
+    parent: Slot<Parent>,
+    child: Slot<Child>,
+    out: (i32, i32),
+    /// How many times the root has told that its output changed.
+    notified: Rc<Cell<usize>>,
+    _root: LiveStateComponent<Parent>,
+    _subscription: regui::ListenerGuard,
+}
+
+impl App {
+    fn new() -> Self {
+        let log = Log::default();
+        let parent = Slot::default();
+        let child = Slot::default();
+        let (out, root) = LiveStateComponent::<Parent>::build((log.clone(), parent.clone(), child.clone()));
+        let (out, emitter) = out.into_tuple();
+        let notified = Rc::new(Cell::new(0));
+        let subscription = emitter.listen({
+            let notified = notified.clone();
+            move || notified.set(notified.get() + 1)
+        });
+        Self { log, parent, child, out, notified, _root: root, _subscription: subscription }
+    }
+
+    fn take_log(&self) -> Vec<&'static str> {
+        std::mem::take(&mut *self.log.borrow_mut())
+    }
+
+    fn parent(&self, message: ParentMessage) {
+        let link = self.parent.borrow().clone().unwrap();
+        link.send_message(message);
+    }
+
+    fn child(&self, own: i32) {
+        let link = self.child.borrow().clone().unwrap();
+        link.send_message(own);
+    }
+}
+
+#[test]
+fn the_initial_build_renders_once() {
+    let app = App::new();
+    assert_eq!(app.take_log(), ["parent", "child"]);
+    assert_eq!(app.out, (0, 0));
+}
+
+#[test]
+fn unchanged_props_skip_the_child_view() {
+    let app = App::new();
+    app.take_log();
+
+    app.parent(ParentMessage::SetUnrelated(1));
+    assert_eq!(app.take_log(), ["parent"]);
+}
+
+#[test]
+fn changed_props_render_the_child_once() {
+    let app = App::new();
+    app.take_log();
+
+    app.parent(ParentMessage::SetChildValue(5));
+    assert_eq!(app.take_log(), ["parent", "child"]);
+}
+
+#[test]
+fn child_updates_render_the_parent_once() {
+    let app = App::new();
+    app.take_log();
+
+    app.child(3);
+    assert_eq!(app.take_log(), ["child", "parent"]);
+
+    // the child output does not change, the parent is not rendered
+    app.child(3);
+    assert_eq!(app.take_log(), ["child"]);
+}
+
+#[test]
+fn the_root_is_notified_when_its_output_changes() {
+    let app = App::new();
+
+    app.parent(ParentMessage::SetUnrelated(0));
+    assert_eq!(app.notified.get(), 0);
+
+    app.parent(ParentMessage::SetChildValue(5));
+    assert_eq!(app.notified.get(), 1);
+}